@@ -20,6 +20,10 @@ const SETTING_REORDER_CODE = "reorder_code"
 const SETTING_SAFE_MODE = "safe_mode"
 const SETTING_FORMATTER_PATH = "formatter_path"
 
+const PROJECT_CONFIG_FILE_PATH = "res://gdformat.toml"
+## Exit code of the formatter when the code to format has syntax errors.
+const EXIT_CODE_SYNTAX_ERRORS = 3
+
 const COMMAND_PALETTE_CATEGORY = "gdquest gdscript formatter/"
 const COMMAND_PALETTE_FORMAT_SCRIPT = "Format GDScript"
 const COMMAND_PALETTE_INSTALL_UPDATE = "Install or Update Formatter"
@@ -364,6 +368,22 @@ func has_editor_setting(setting_name: String) -> bool:
 	return editor_settings.has_setting(full_setting_key)
 
 
+## Returns true if the gdformat.toml configuration file at the root of the project
+## sets the indentation in its [format] table.
+func project_config_sets_indentation() -> bool:
+	if not FileAccess.file_exists(PROJECT_CONFIG_FILE_PATH):
+		return false
+
+	var is_in_format_table := false
+	for line in FileAccess.get_file_as_string(PROJECT_CONFIG_FILE_PATH).split("\n"):
+		var stripped_line := line.strip_edges()
+		if stripped_line.begins_with("["):
+			is_in_format_table = stripped_line.trim_suffix("]").trim_prefix("[").strip_edges() == "format"
+		elif is_in_format_table and (stripped_line.begins_with("use_spaces") or stripped_line.begins_with("indent_size")):
+			return true
+	return false
+
+
 ## Formats a GDScript file using the GDScript Formatter,
 ## and returns the formatted code as a string. Optionally reorders the code.
 func format_code(script: GDScript, force_reorder := false) -> String:
@@ -371,20 +391,16 @@ func format_code(script: GDScript, force_reorder := false) -> String:
 	var output: Array = []
 	var formatter_arguments: Array = [ProjectSettings.globalize_path(script_path)]
 
-	if get_editor_setting(SETTING_USE_SPACES):
+	# If the project's configuration file sets the indentation, the formatter reads it from there.
+	if not project_config_sets_indentation() and get_editor_setting(SETTING_USE_SPACES):
 		formatter_arguments.push_back("--use-spaces")
 		formatter_arguments.push_back("--indent-size=%d" % get_editor_setting(SETTING_INDENT_SIZE))
 
 	var should_reorder := force_reorder or get_editor_setting(SETTING_REORDER_CODE) as bool
-	# TODO: remove this safety check once we have safe mode support for reorder_code
-	if should_reorder and get_editor_setting(SETTING_SAFE_MODE):
-		push_error("GDScript Formatter: Settings 'reorder_code' and 'safe_mode' settings are incompatible and cannot be used together.")
-		return ""
-
 	if should_reorder:
 		formatter_arguments.push_back("--reorder-code")
 
-	if not force_reorder and get_editor_setting(SETTING_SAFE_MODE):
+	if get_editor_setting(SETTING_SAFE_MODE):
 		formatter_arguments.push_back("--safe")
 
 	var exit_code := OS.execute(get_editor_setting(SETTING_FORMATTER_PATH), formatter_arguments, output)
@@ -399,7 +415,10 @@ func format_code(script: GDScript, force_reorder := false) -> String:
 	else:
 		push_error("Format GDScript failed: " + script_path)
 		push_error("\tExit code: " + str(exit_code) + " Output: " + (output.front().strip_edges() if output.size() > 0 else "No output"))
-		push_error('\tIf your script does not have any syntax errors, this might be a formatter bug.')
+		if exit_code == EXIT_CODE_SYNTAX_ERRORS:
+			push_error("\tThe script has syntax errors. Fix them and try again.")
+		else:
+			push_error('\tIf your script does not have any syntax errors, this might be a formatter bug.')
 		return ""
 
 
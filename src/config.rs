@@ -0,0 +1,445 @@
+//! This module loads the formatter and linter settings from project
+//! configuration files named `gdformat.toml`.
+//!
+//! To find the settings that apply to a GDScript file, we walk up the
+//! directory tree from the file's folder, collecting every configuration file
+//! until we reach the Godot project root (the folder containing
+//! `project.godot`) or the root of the filesystem. Files closer to the
+//! GDScript file take precedence, which lets a subfolder like `addons/` use
+//! different settings from the rest of the project.
+//!
+//! Options passed on the command line always take precedence over values
+//! found in configuration files.
+//!
+//! Example configuration file:
+//!
+//! ```toml
+//! [format]
+//! use_spaces = true
+//! indent_size = 4
+//! line_width = 100
+//!
+//! [lint]
+//! disabled_rules = ["private-access"]
+//! max_line_length = 120
+//! max_function_length = 80
+//!
+//! [lint.rules.function-name]
+//! severity = "warning"
+//! pattern = "^_?[a-z][a-zA-Z0-9]*$"
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::FormatterConfig;
+use crate::linter::LinterConfig;
+use crate::linter::rule_config::{RuleSettings, validate_rule_names, validate_rule_settings};
+
+/// Name of the configuration file we look for in each directory.
+pub const CONFIG_FILE_NAME: &str = "gdformat.toml";
+/// We stop searching for configuration files when we reach a directory
+/// containing this file, as it marks the root of a Godot project.
+const PROJECT_FILE_NAME: &str = "project.godot";
+
+/// The content of a configuration file. Every value is optional so that
+/// files can be layered on top of each other and only override what they set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub format: FormatOptions,
+    pub lint: LintOptions,
+}
+
+/// Formatter settings, mirroring the fields of `FormatterConfig`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatOptions {
+    pub use_spaces: Option<bool>,
+    pub indent_size: Option<usize>,
+    pub reorder_code: Option<bool>,
+    pub safe: Option<bool>,
+    pub strict_safe: Option<bool>,
+    pub line_width: Option<usize>,
+}
+
+/// Linter settings, mirroring the fields of `LinterConfig`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintOptions {
+    pub disabled_rules: Option<HashSet<String>>,
+    pub max_line_length: Option<usize>,
+    pub max_function_length: Option<usize>,
+    pub max_file_length: Option<usize>,
+    pub max_parameters: Option<usize>,
+    pub max_nesting_depth: Option<usize>,
+    pub max_returns: Option<usize>,
+    pub max_cyclomatic_complexity: Option<usize>,
+    pub rules: Option<HashMap<String, RuleSettings>>,
+}
+
+impl ConfigFile {
+    /// Reads and parses a configuration file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+        toml::from_str(&content)
+            .map_err(|error| format!("Failed to parse {}: {}", path.display(), error))
+    }
+
+    /// Overrides the values of this configuration with the ones set in `other`.
+    pub fn merge(&mut self, other: &ConfigFile) {
+        self.format.merge(&other.format);
+        self.lint.merge(&other.lint);
+    }
+}
+
+impl FormatOptions {
+    /// Overrides the values of these options with the ones set in `other`.
+    pub fn merge(&mut self, other: &FormatOptions) {
+        if other.use_spaces.is_some() {
+            self.use_spaces = other.use_spaces;
+        }
+        if other.indent_size.is_some() {
+            self.indent_size = other.indent_size;
+        }
+        if other.reorder_code.is_some() {
+            self.reorder_code = other.reorder_code;
+        }
+        if other.safe.is_some() {
+            self.safe = other.safe;
+        }
+        if other.strict_safe.is_some() {
+            self.strict_safe = other.strict_safe;
+        }
+        if other.line_width.is_some() {
+            self.line_width = other.line_width;
+        }
+    }
+
+    /// Returns the formatter configuration with these options applied on top
+    /// of the default values.
+    pub fn to_formatter_config(&self) -> FormatterConfig {
+        let default = FormatterConfig::default();
+        FormatterConfig {
+            indent_size: self.indent_size.unwrap_or(default.indent_size),
+            use_spaces: self.use_spaces.unwrap_or(default.use_spaces),
+            reorder_code: self.reorder_code.unwrap_or(default.reorder_code),
+            safe: self.safe.unwrap_or(default.safe),
+            strict_safe: self.strict_safe.unwrap_or(default.strict_safe),
+            line_width: self.line_width.unwrap_or(default.line_width),
+        }
+    }
+}
+
+impl LintOptions {
+    /// Overrides the values of these options with the ones set in `other`.
+    pub fn merge(&mut self, other: &LintOptions) {
+        if other.disabled_rules.is_some() {
+            self.disabled_rules = other.disabled_rules.clone();
+        }
+        if other.max_line_length.is_some() {
+            self.max_line_length = other.max_line_length;
+        }
+        if other.max_function_length.is_some() {
+            self.max_function_length = other.max_function_length;
+        }
+        if other.max_file_length.is_some() {
+            self.max_file_length = other.max_file_length;
+        }
+        if other.max_parameters.is_some() {
+            self.max_parameters = other.max_parameters;
+        }
+        if other.max_nesting_depth.is_some() {
+            self.max_nesting_depth = other.max_nesting_depth;
+        }
+        if other.max_returns.is_some() {
+            self.max_returns = other.max_returns;
+        }
+        if other.max_cyclomatic_complexity.is_some() {
+            self.max_cyclomatic_complexity = other.max_cyclomatic_complexity;
+        }
+        // Rule settings are merged rule by rule and option by option, so that
+        // a subfolder can change one option without repeating the others
+        if let Some(other_rules) = &other.rules {
+            let rules = self.rules.get_or_insert_with(HashMap::new);
+            for (rule_name, settings) in other_rules {
+                rules.entry(rule_name.clone()).or_default().merge(settings);
+            }
+        }
+    }
+
+    /// Returns the linter configuration with these options applied on top of
+    /// the default values.
+    pub fn to_linter_config(&self) -> LinterConfig {
+        let default = LinterConfig::default();
+        LinterConfig {
+            disabled_rules: self
+                .disabled_rules
+                .clone()
+                .unwrap_or(default.disabled_rules),
+            max_line_length: self.max_line_length.unwrap_or(default.max_line_length),
+            max_function_length: self
+                .max_function_length
+                .unwrap_or(default.max_function_length),
+            max_file_length: self.max_file_length.unwrap_or(default.max_file_length),
+            max_parameters: self.max_parameters.unwrap_or(default.max_parameters),
+            max_nesting_depth: self.max_nesting_depth.unwrap_or(default.max_nesting_depth),
+            max_returns: self.max_returns.unwrap_or(default.max_returns),
+            max_cyclomatic_complexity: self
+                .max_cyclomatic_complexity
+                .unwrap_or(default.max_cyclomatic_complexity),
+            rules: self.rules.clone().unwrap_or(default.rules),
+        }
+    }
+}
+
+/// Finds and merges the configuration files that apply to a given path. We
+/// cache the merged configuration of each directory, as we usually process
+/// many files from the same folders.
+#[derive(Default)]
+pub struct ConfigResolver {
+    directory_configs: HashMap<PathBuf, ConfigFile>,
+}
+
+impl ConfigResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the formatter configuration for `path`: the project
+    /// configuration files with the `overrides` applied on top.
+    pub fn formatter_config(
+        &mut self,
+        path: &Path,
+        overrides: &FormatOptions,
+    ) -> Result<FormatterConfig, String> {
+        let mut options = self.resolve(path)?.format;
+        options.merge(overrides);
+        Ok(options.to_formatter_config())
+    }
+
+    /// Returns the linter configuration for `path`: the project configuration
+    /// files with the `overrides` applied on top.
+    pub fn linter_config(
+        &mut self,
+        path: &Path,
+        overrides: &LintOptions,
+    ) -> Result<LinterConfig, String> {
+        let mut options = self.resolve(path)?.lint;
+        options.merge(overrides);
+        if let Some(disabled_rules) = &options.disabled_rules
+            && let Err(invalid_rules) = validate_rule_names(disabled_rules)
+        {
+            return Err(format!(
+                "Invalid rule names in the configuration for {}: {}",
+                path.display(),
+                invalid_rules.join(", ")
+            ));
+        }
+        let config = options.to_linter_config();
+        validate_rule_settings(&config.rules, &config).map_err(|error| {
+            format!(
+                "Invalid rule settings in the configuration for {}: {}",
+                path.display(),
+                error
+            )
+        })?;
+        Ok(config)
+    }
+
+    /// Returns the merged configuration that applies to `path`, which can be
+    /// a file or a directory.
+    pub fn resolve(&mut self, path: &Path) -> Result<ConfigFile, String> {
+        let path = std::path::absolute(path)
+            .map_err(|error| format!("Failed to resolve path {}: {}", path.display(), error))?;
+        let directory = if path.is_dir() {
+            path.as_path()
+        } else {
+            path.parent().unwrap_or(Path::new("/"))
+        };
+        self.resolve_directory(directory)
+    }
+
+    fn resolve_directory(&mut self, directory: &Path) -> Result<ConfigFile, String> {
+        if let Some(config) = self.directory_configs.get(directory) {
+            return Ok(config.clone());
+        }
+
+        // We start from the configuration of the parent directory, unless we
+        // reached the root of the Godot project
+        let mut config = match directory.parent() {
+            Some(parent) if !directory.join(PROJECT_FILE_NAME).is_file() => {
+                self.resolve_directory(parent)?
+            }
+            _ => ConfigFile::default(),
+        };
+
+        let config_path = directory.join(CONFIG_FILE_NAME);
+        if config_path.is_file() {
+            config.merge(&ConfigFile::load(&config_path)?);
+        }
+
+        self.directory_configs
+            .insert(directory.to_path_buf(), config.clone());
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linter::rule_config::RuleSeverity;
+
+    /// Creates an empty temporary directory unique to the calling test.
+    fn create_test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "gdscript_formatter_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_nested_config_files() {
+        let directory = create_test_directory("nested_config");
+        let project = directory.join("project");
+        let addon = project.join("addons/third_party");
+        fs::create_dir_all(&addon).unwrap();
+        fs::write(project.join(PROJECT_FILE_NAME), "").unwrap();
+        // This file is outside of the project and must not be used
+        fs::write(
+            directory.join(CONFIG_FILE_NAME),
+            "[format]\nreorder_code = true\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join(CONFIG_FILE_NAME),
+            "[format]\nuse_spaces = true\nindent_size = 2\n\n[lint]\nmax_line_length = 120\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join("addons").join(CONFIG_FILE_NAME),
+            "[format]\nuse_spaces = false\n\n[lint]\ndisabled_rules = [\"class-name\"]\n",
+        )
+        .unwrap();
+
+        let mut resolver = ConfigResolver::new();
+        let no_overrides = FormatOptions::default();
+
+        let config = resolver
+            .formatter_config(&project.join("player.gd"), &no_overrides)
+            .unwrap();
+        assert!(config.use_spaces);
+        assert_eq!(config.indent_size, 2);
+        assert!(!config.reorder_code);
+
+        let config = resolver
+            .formatter_config(&addon.join("plugin.gd"), &no_overrides)
+            .unwrap();
+        assert!(!config.use_spaces);
+        assert_eq!(config.indent_size, 2);
+
+        let lint_config = resolver
+            .linter_config(&addon.join("plugin.gd"), &LintOptions::default())
+            .unwrap();
+        assert_eq!(lint_config.max_line_length, 120);
+        assert!(lint_config.disabled_rules.contains("class-name"));
+
+        // Command line options take precedence over configuration files
+        let overrides = FormatOptions {
+            indent_size: Some(8),
+            ..Default::default()
+        };
+        let config = resolver
+            .formatter_config(&addon.join("plugin.gd"), &overrides)
+            .unwrap();
+        assert_eq!(config.indent_size, 8);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_rule_settings() {
+        let directory = create_test_directory("rule_settings");
+        let subfolder = directory.join("enemies");
+        fs::create_dir_all(&subfolder).unwrap();
+        fs::write(directory.join(PROJECT_FILE_NAME), "").unwrap();
+        fs::write(
+            directory.join(CONFIG_FILE_NAME),
+            "[lint.rules.function-name]\nseverity = \"warning\"\npattern = \"^[a-z]+$\"\n",
+        )
+        .unwrap();
+        fs::write(
+            subfolder.join(CONFIG_FILE_NAME),
+            "[lint.rules.function-name]\nseverity = \"off\"\n",
+        )
+        .unwrap();
+
+        let mut resolver = ConfigResolver::new();
+        let config = resolver
+            .linter_config(&subfolder.join("enemy.gd"), &LintOptions::default())
+            .unwrap();
+        let settings = &config.rules["function-name"];
+        assert_eq!(settings.severity, Some(RuleSeverity::Off));
+        assert_eq!(
+            settings
+                .options
+                .get("pattern")
+                .and_then(|value| value.as_str()),
+            Some("^[a-z]+$")
+        );
+
+        // Options must exist and have valid values
+        for content in [
+            "[lint.rules.function-name]\nmax_length = 10\n",
+            "[lint.rules.function-name]\npattern = \"[a-z\"\n",
+            "[lint.rules.class-name]\nseverity = \"fatal\"\n",
+        ] {
+            fs::write(subfolder.join(CONFIG_FILE_NAME), content).unwrap();
+            let mut resolver = ConfigResolver::new();
+            assert!(
+                resolver
+                    .linter_config(&subfolder, &LintOptions::default())
+                    .is_err(),
+                "{}",
+                content
+            );
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config_file() {
+        let directory = create_test_directory("invalid_config");
+        fs::write(directory.join(PROJECT_FILE_NAME), "").unwrap();
+        fs::write(
+            directory.join(CONFIG_FILE_NAME),
+            "[format]\nunknown_option = true\n",
+        )
+        .unwrap();
+
+        let mut resolver = ConfigResolver::new();
+        assert!(resolver.resolve(&directory).is_err());
+
+        fs::write(
+            directory.join(CONFIG_FILE_NAME),
+            "[lint]\ndisabled_rules = [\"not-a-rule\"]\n",
+        )
+        .unwrap();
+        let mut resolver = ConfigResolver::new();
+        assert!(
+            resolver
+                .linter_config(&directory, &LintOptions::default())
+                .is_err()
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
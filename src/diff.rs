@@ -0,0 +1,133 @@
+//! This module compares input code with the formatter's output to show what
+//! the formatter would change. It's used by the --diff command line option.
+//!
+//! We support two output formats: a unified diff for people reading CI logs,
+//! and a JSON list of changed line ranges for tools.
+use serde::Serialize;
+use similar::TextDiff;
+
+/// Number of unchanged lines to show around each change in unified diffs.
+const CONTEXT_LINES: usize = 3;
+
+/// The changes the formatter would make to a file, as line ranges.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChanges {
+    pub file: String,
+    pub changes: Vec<LineRangeChange>,
+}
+
+/// A block of lines that changed between the original code and the formatted
+/// code. Line numbers start at 1, and a line count of 0 means that lines were
+/// only inserted or only removed at this position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineRangeChange {
+    pub original_start: usize,
+    pub original_lines: usize,
+    pub formatted_start: usize,
+    pub formatted_lines: usize,
+}
+
+/// Returns a unified diff between `original` and `formatted`, with the file
+/// path in the headers. Returns an empty string if the two are identical.
+pub fn unified_diff(file_path: &str, original: &str, formatted: &str, with_colors: bool) -> String {
+    if original == formatted {
+        return String::new();
+    }
+
+    let diff = TextDiff::from_lines(original, formatted);
+    let output = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{}", file_path), &format!("b/{}", file_path))
+        .to_string();
+
+    if with_colors {
+        colorize_unified_diff(&output)
+    } else {
+        output
+    }
+}
+
+/// Returns the ranges of lines that differ between `original` and `formatted`.
+pub fn changed_line_ranges(original: &str, formatted: &str) -> Vec<LineRangeChange> {
+    let diff = TextDiff::from_lines(original, formatted);
+    diff.grouped_ops(0)
+        .iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let original_range = first.old_range().start..last.old_range().end;
+            let formatted_range = first.new_range().start..last.new_range().end;
+            Some(LineRangeChange {
+                original_start: original_range.start + 1,
+                original_lines: original_range.len(),
+                formatted_start: formatted_range.start + 1,
+                formatted_lines: formatted_range.len(),
+            })
+        })
+        .collect()
+}
+
+/// Adds terminal colors to a unified diff: red for removed lines, green for
+/// added lines, and cyan for hunk headers.
+fn colorize_unified_diff(diff: &str) -> String {
+    let mut output = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let color = if line.starts_with("---") || line.starts_with("+++") {
+            "\x1b[1m"
+        } else if line.starts_with("@@") {
+            "\x1b[36m"
+        } else if line.starts_with('-') {
+            "\x1b[31m"
+        } else if line.starts_with('+') {
+            "\x1b[32m"
+        } else {
+            output.push_str(line);
+            continue;
+        };
+        let (content, line_ending) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        output.push_str(color);
+        output.push_str(content);
+        output.push_str("\x1b[0m");
+        output.push_str(line_ending);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let original = "var a=1\nvar b = 2\n";
+        let formatted = "var a = 1\nvar b = 2\n";
+
+        let diff = unified_diff("test.gd", original, formatted, false);
+        assert_eq!(
+            diff,
+            "--- a/test.gd\n+++ b/test.gd\n@@ -1,2 +1,2 @@\n-var a=1\n+var a = 1\n var b = 2\n"
+        );
+        assert!(unified_diff("test.gd", formatted, formatted, false).is_empty());
+    }
+
+    #[test]
+    fn test_changed_line_ranges() {
+        let original = "func a():\n\tpass\nfunc b():\n\tpass\n";
+        let formatted = "func a():\n\tpass\n\n\nfunc b():\n\tpass\n";
+
+        let changes = changed_line_ranges(original, formatted);
+        assert_eq!(
+            changes,
+            vec![LineRangeChange {
+                original_start: 3,
+                original_lines: 0,
+                formatted_start: 3,
+                formatted_lines: 2,
+            }]
+        );
+    }
+}
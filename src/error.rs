@@ -0,0 +1,210 @@
+//! Errors returned by the formatter's and the linter's library API.
+//!
+//! Each kind of formatting error has its own exit code in the command line
+//! interface, so that editor plugins and scripts can tell code with syntax
+//! errors apart from a bug in the formatter.
+use std::fmt;
+
+use tree_sitter::{Node, Tree};
+
+use crate::linter::lib::get_line_column;
+
+/// An error that prevents formatting or reordering a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// The input code has syntax errors. The formatter tolerates most syntax
+    /// errors, so we only return this error when formatting fails on code
+    /// that has some.
+    Parse(Vec<SyntaxError>),
+    /// Topiary failed to format the code.
+    Topiary(String),
+    /// The formatted code doesn't match the input code: safe mode found a
+    /// different code structure, or regions with formatting turned off were
+    /// lost. Contains a report of the difference.
+    Divergence(String),
+    /// The code or the output of Topiary is not valid UTF-8.
+    Utf8(String),
+    /// Reordering the code failed.
+    Reorder(String),
+    /// A range of lines to format starts at line 0 or after its end.
+    InvalidLineRange { start: usize, end: usize },
+}
+
+impl FormatError {
+    /// Returns the exit code of the command line interface for this error.
+    /// The exit code 1 is for files that are not formatted and other errors,
+    /// and 2 is for invalid command line arguments.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FormatError::Parse(_) => 3,
+            FormatError::Topiary(_) => 4,
+            FormatError::Divergence(_) => 5,
+            FormatError::Utf8(_) => 6,
+            FormatError::Reorder(_) => 7,
+            FormatError::InvalidLineRange { .. } => 8,
+        }
+    }
+
+    /// Moves the positions of syntax errors down by `line_offset` lines, for
+    /// errors found in a part of a file.
+    pub(crate) fn with_line_offset(self, line_offset: usize) -> Self {
+        match self {
+            FormatError::Parse(errors) => FormatError::Parse(
+                errors
+                    .into_iter()
+                    .map(|error| SyntaxError {
+                        line: error.line + line_offset,
+                        ..error
+                    })
+                    .collect(),
+            ),
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Parse(errors) => {
+                write!(f, "The code has syntax errors:")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            FormatError::Topiary(message) => write!(f, "Topiary formatting failed: {}", message),
+            FormatError::Divergence(report) => write!(f, "{}", report),
+            FormatError::Utf8(message) => write!(f, "Invalid UTF-8: {}", message),
+            FormatError::Reorder(message) => write!(f, "Code reordering failed: {}", message),
+            FormatError::InvalidLineRange { start, end } => write!(
+                f,
+                "Invalid line range {}:{}, lines start at 1 and the start can't be after the end",
+                start, end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// An error that prevents linting a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintError {
+    /// Tree-sitter couldn't load the GDScript grammar or parse the code.
+    Parser(String),
+    /// The configuration has invalid settings for a rule, like a naming
+    /// pattern that isn't a valid regular expression.
+    RuleSettings { rule: &'static str, message: String },
+}
+
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintError::Parser(message) => write!(f, "{}", message),
+            LintError::RuleSettings { rule, message } => {
+                write!(f, "Invalid settings for rule '{}': {}", rule, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LintError {}
+
+/// The position of an ERROR or MISSING node in the syntax tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    /// Line and column of the start of the node, starting at 1.
+    pub line: usize,
+    pub column: usize,
+    /// Kind of node that the parser expected but didn't find, for MISSING
+    /// nodes. None for ERROR nodes, which contain code the parser couldn't
+    /// make sense of.
+    pub missing_kind: Option<&'static str>,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.missing_kind {
+            Some(kind) => write!(
+                f,
+                "line {}, column {}: missing {}",
+                self.line, self.column, kind
+            ),
+            None => write!(
+                f,
+                "line {}, column {}: unexpected code",
+                self.line, self.column
+            ),
+        }
+    }
+}
+
+/// Returns the syntax errors in the tree, in the order of the code.
+pub fn find_syntax_errors(tree: &Tree) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    collect_syntax_errors(&tree.root_node(), &mut errors);
+    errors
+}
+
+fn collect_syntax_errors(node: &Node, errors: &mut Vec<SyntaxError>) {
+    if node.is_error() || node.is_missing() {
+        let (line, column) = get_line_column(node);
+        errors.push(SyntaxError {
+            line,
+            column,
+            missing_kind: node.is_missing().then(|| node.kind()),
+        });
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(&child, errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use tree_sitter::Parser;
+
+    #[test]
+    fn test_find_syntax_errors() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+            .unwrap();
+        let valid_tree = parser.parse("func a():\n\tpass\n", None).unwrap();
+        assert!(find_syntax_errors(&valid_tree).is_empty());
+
+        let invalid_tree = parser
+            .parse("func a():\n\tpass\n\n\nfunc b(:\n\tpass\n", None)
+            .unwrap();
+        let errors = find_syntax_errors(&invalid_tree);
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| error.line >= 5));
+    }
+
+    #[test]
+    fn test_format_error_exit_codes() {
+        let errors = [
+            FormatError::Parse(Vec::new()),
+            FormatError::Topiary(String::new()),
+            FormatError::Divergence(String::new()),
+            FormatError::Utf8(String::new()),
+            FormatError::Reorder(String::new()),
+            FormatError::InvalidLineRange { start: 0, end: 0 },
+        ];
+        // The command line interface uses 1 for unformatted files and other
+        // errors, and 2 for invalid arguments
+        let exit_codes: HashSet<i32> = errors.iter().map(FormatError::exit_code).collect();
+        assert_eq!(exit_codes.len(), errors.len());
+        assert!(!exit_codes.contains(&1));
+        assert!(!exit_codes.contains(&2));
+    }
+}
@@ -0,0 +1,130 @@
+//! This module finds the GDScript files to process from the paths passed on
+//! the command line.
+//!
+//! Files are used as-is, while directories are walked recursively. When walking
+//! a directory, we skip what Godot itself ignores (the `.godot/` cache folder
+//! and folders containing a `.gdignore` file) as well as paths matched by
+//! `.gitignore` files.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Name of the folder where Godot stores its import cache and editor data.
+const GODOT_CACHE_DIRECTORY: &str = ".godot";
+/// Godot skips any folder containing a file with this name when importing
+/// resources and scanning scripts.
+const GDIGNORE_FILE_NAME: &str = ".gdignore";
+
+/// Returns all the GDScript files found in `paths`, in a stable order.
+///
+/// Files passed explicitly are kept even if they would be ignored while
+/// walking a directory, as long as they have the `.gd` extension.
+pub fn find_gdscript_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut found_files = Vec::new();
+    let mut seen_files = HashSet::new();
+
+    for path in paths {
+        if !path.is_dir() {
+            if is_gdscript_file(path) && seen_files.insert(path.clone()) {
+                found_files.push(path.clone());
+            }
+            continue;
+        }
+
+        let walker = WalkBuilder::new(path)
+            // Godot projects are often not at the root of a git repository, so
+            // we want .gitignore files to apply even outside of one
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|entry| {
+                let is_dir = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir());
+                !is_dir || !is_ignored_by_godot(entry.path())
+            })
+            .build();
+
+        for entry in walker {
+            let entry = entry.map_err(|error| {
+                format!("Failed to read directory {}: {}", path.display(), error)
+            })?;
+            let entry_path = entry.path();
+            let is_file = entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file());
+            if is_file
+                && is_gdscript_file(entry_path)
+                && seen_files.insert(entry_path.to_path_buf())
+            {
+                found_files.push(entry_path.to_path_buf());
+            }
+        }
+    }
+
+    Ok(found_files)
+}
+
+fn is_gdscript_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "gd")
+}
+
+/// Returns true if Godot would skip this directory: either it's the project's
+/// cache folder or it contains a .gdignore file.
+fn is_ignored_by_godot(directory: &Path) -> bool {
+    directory
+        .file_name()
+        .is_some_and(|name| name == GODOT_CACHE_DIRECTORY)
+        || directory.join(GDIGNORE_FILE_NAME).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Creates an empty temporary directory unique to the calling test.
+    fn create_test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "gdscript_formatter_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn write_file(path: PathBuf) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn test_find_gdscript_files_in_project() {
+        let project = create_test_directory("find_files");
+        write_file(project.join("project.godot"));
+        write_file(project.join("player.gd"));
+        write_file(project.join("player.tscn"));
+        write_file(project.join("enemies/enemy.gd"));
+        write_file(project.join(".godot/editor/cached.gd"));
+        write_file(project.join("addons/third_party/.gdignore"));
+        write_file(project.join("addons/third_party/plugin.gd"));
+        write_file(project.join("build/generated.gd"));
+        fs::write(project.join(".gitignore"), "build/\n").unwrap();
+
+        let files = find_gdscript_files(std::slice::from_ref(&project)).unwrap();
+        assert_eq!(
+            files,
+            vec![project.join("enemies/enemy.gd"), project.join("player.gd")]
+        );
+
+        // Files passed explicitly are always processed
+        let explicit_file = project.join("build/generated.gd");
+        let files =
+            find_gdscript_files(&[explicit_file.clone(), project.join("player.tscn")]).unwrap();
+        assert_eq!(files, vec![explicit_file]);
+
+        fs::remove_dir_all(project).unwrap();
+    }
+}
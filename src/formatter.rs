@@ -4,44 +4,168 @@
 //! After the main formatting pass through Topiary, we apply post-processing steps
 //! to clean up and standardize the output. These include:
 //!
+//! - Wrapping lines that are longer than the configured line width
 //! - Adding vertical spacing between methods, classes, etc.
 //! - Removing unnecessary blank lines that might have been added during formatting
 //! - Removing dangling semicolons that sometimes end up on their own lines
 //! - Cleaning up lines that contain only whitespace
+//! - Restoring the code in regions where formatting is turned off with
+//!   `# fmt: off` or `# fmt: skip` comments
 //! - Optionally reordering code elements according to the GDScript style guide
 //!
 //! Some of the post-processing is outside of Topiary's capabilities, while other
 //! rules have too much performance overhead when applied through Topiary.
-use std::{collections::VecDeque, io::BufWriter};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::BufWriter,
+    ops::Range,
+    str::FromStr,
+};
 
 use regex::{Regex, RegexBuilder, Replacer};
 use topiary_core::{Language, Operation, TopiaryQuery, formatter_tree};
-use tree_sitter::{Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
+use tree_sitter::{Node, Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
 
 use crate::FormatterConfig;
+use crate::error::{FormatError, SyntaxError, find_syntax_errors};
+
+use directives::SkippedRegion;
+
+mod directives;
 
 static QUERY: &str = include_str!("../queries/gdscript.scm");
 
-pub fn format_gdscript(content: &str) -> Result<String, Box<dyn std::error::Error>> {
+/// Maximum number of times we wrap long lines and run Topiary again. Each pass
+/// wraps nested constructs one level deeper.
+const MAX_LINE_WRAPPING_PASSES: usize = 10;
+
+pub fn format_gdscript(content: &str) -> Result<String, FormatError> {
     format_gdscript_with_config(content, &FormatterConfig::default())
 }
 
 pub fn format_gdscript_with_config(
     content: &str,
     config: &FormatterConfig,
-) -> Result<String, Box<dyn std::error::Error>> {
+) -> Result<String, FormatError> {
     let mut formatter = Formatter::new(content.to_owned(), config.clone());
 
-    formatter.preprocess().format()?.postprocess().reorder();
+    formatter
+        .preprocess()
+        .format()?
+        .wrap_long_lines()?
+        .postprocess()
+        .restore_skipped_regions()?
+        .reorder()?;
     formatter.finish()
 }
 
+/// A range of lines to format. Line numbers start at 1 and both ends are
+/// included, like in code editors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    /// Parses a line range written as "START:END", for example "10:40".
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid line range \"{value}\", expected START:END"))?;
+        let parse_line = |line: &str| {
+            line.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&line| line > 0)
+                .ok_or_else(|| format!("Invalid line number \"{line}\" in range \"{value}\""))
+        };
+        let range = LineRange {
+            start: parse_line(start)?,
+            end: parse_line(end)?,
+        };
+        if range.start > range.end {
+            return Err(format!(
+                "Invalid line range \"{value}\", the start is after the end"
+            ));
+        }
+        Ok(range)
+    }
+}
+
+/// Formats only the given lines of `content`, leaving the rest of the code
+/// byte-identical. See `format_gdscript_ranges()` for details.
+pub fn format_gdscript_lines(
+    content: &str,
+    config: &FormatterConfig,
+    lines: &[LineRange],
+) -> Result<String, FormatError> {
+    if let Some(range) = lines
+        .iter()
+        .find(|range| range.start == 0 || range.start > range.end)
+    {
+        return Err(FormatError::InvalidLineRange {
+            start: range.start,
+            end: range.end,
+        });
+    }
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let get_line_start = |line: usize| line_starts.get(line).copied().unwrap_or(content.len());
+
+    let byte_ranges: Vec<Range<usize>> = lines
+        .iter()
+        .map(|range| get_line_start(range.start - 1)..get_line_start(range.end))
+        .collect();
+    format_gdscript_ranges(content, config, &byte_ranges)
+}
+
+/// Formats only the given byte ranges of `content`, leaving the rest of the
+/// code byte-identical.
+///
+/// We can only format complete top-level statements, so each range grows to
+/// the smallest set of top-level statements (functions, classes, variables,
+/// etc.) that contains it. Reordering code is not supported here as it moves
+/// statements across the whole file.
+pub fn format_gdscript_ranges(
+    content: &str,
+    config: &FormatterConfig,
+    ranges: &[Range<usize>],
+) -> Result<String, FormatError> {
+    let config = FormatterConfig {
+        reorder_code: false,
+        ..config.clone()
+    };
+
+    let formatter = Formatter::new(content.to_owned(), config.clone());
+    let spans = formatter.find_top_level_spans(ranges);
+
+    // We replace spans from the end of the file so that byte positions stay
+    // valid as we modify the content
+    let mut output = content.to_owned();
+    for span in spans.into_iter().rev() {
+        let formatted = format_gdscript_with_config(&content[span.clone()], &config)
+            .map_err(|error| error.with_line_offset(content[..span.start].matches('\n').count()))?;
+        output.replace_range(span, &formatted);
+    }
+    Ok(output)
+}
+
 struct Formatter {
     content: String,
     config: FormatterConfig,
     parser: Parser,
     input_tree: GdTree,
     tree: Tree,
+    /// The code before formatting and the regions of it where formatting is
+    /// turned off with directive comments.
+    input_content: String,
+    skipped_regions: Vec<SkippedRegion>,
+    input_syntax_errors: Vec<SyntaxError>,
 }
 
 impl Formatter {
@@ -54,8 +178,13 @@ impl Formatter {
         let tree = parser.parse(&content, None).unwrap();
         let mut input_tree = GdTree::from_ts_tree(&tree, content.as_bytes());
         input_tree.postprocess();
+        let skipped_regions = directives::find_skipped_regions(&tree, &content);
+        let input_syntax_errors = find_syntax_errors(&tree);
 
         Self {
+            input_content: content.clone(),
+            skipped_regions,
+            input_syntax_errors,
             content,
             config,
             tree,
@@ -65,7 +194,7 @@ impl Formatter {
     }
 
     #[inline(always)]
-    fn format(&mut self) -> Result<&mut Self, Box<dyn std::error::Error>> {
+    fn format(&mut self) -> Result<&mut Self, FormatError> {
         let indent_string = if self.config.use_spaces {
             " ".repeat(self.config.indent_size)
         } else {
@@ -92,34 +221,202 @@ impl Formatter {
                 tolerate_parsing_errors: true,
             },
         )
-        .map_err(|e| format!("Topiary formatting failed: {e}"))?;
+        .map_err(|e| {
+            // Topiary tolerates syntax errors, but when it fails, they are the
+            // most likely cause
+            if self.input_syntax_errors.is_empty() {
+                FormatError::Topiary(e.to_string())
+            } else {
+                FormatError::Parse(self.input_syntax_errors.clone())
+            }
+        })?;
 
         drop(writer);
 
         self.content = String::from_utf8(output)
-            .map_err(|e| format!("Failed to parse topiary output as UTF-8: {}", e))?;
+            .map_err(|e| FormatError::Utf8(format!("Failed to parse topiary output: {}", e)))?;
 
         Ok(self)
     }
 
+    /// Returns the byte spans of the top-level statements that overlap with
+    /// `ranges`, sorted and merged. Spans cover whole lines, from the start
+    /// of the first statement's line to after the last statement's line break.
+    ///
+    /// Spans also grow to cover the regions with formatting turned off that
+    /// they overlap, along with their directive comments. Otherwise, we would
+    /// format the statements of a region without its `# fmt: off` comment.
+    fn find_top_level_spans(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let root = self.tree.root_node();
+        let mut cursor = root.walk();
+        let statements: Vec<Node> = root.children(&mut cursor).collect();
+        let get_statements_span = |range: &Range<usize>| {
+            // An empty range, like a cursor position, selects the statement
+            // it's in
+            let range_end = range.end.max(range.start + 1);
+            let mut overlapping = statements.iter().filter(|statement| {
+                statement.start_byte() < range_end && range.start < statement.end_byte()
+            });
+            let first = overlapping.next()?;
+            let last = overlapping.next_back().unwrap_or(first);
+            Some(self.get_line_start(first.start_byte())..self.get_line_end(last.end_byte()))
+        };
+
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(self.content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        let get_row_start =
+            |row: usize| line_starts.get(row).copied().unwrap_or(self.content.len());
+        let skipped_spans: Vec<Range<usize>> = self
+            .skipped_regions
+            .iter()
+            .map(|region| {
+                get_row_start(region.directive_rows.start)..get_row_start(region.directive_rows.end)
+            })
+            .collect();
+
+        let mut spans: Vec<Range<usize>> = ranges
+            .iter()
+            .filter_map(|range| {
+                let mut span = get_statements_span(range)?;
+                // Growing the span can make it overlap with more regions, so
+                // we repeat until it stops changing
+                loop {
+                    let mut widened = span.clone();
+                    for skipped in &skipped_spans {
+                        if skipped.start < widened.end && widened.start < skipped.end {
+                            widened.start = widened.start.min(skipped.start);
+                            widened.end = widened.end.max(skipped.end);
+                        }
+                    }
+                    let widened = get_statements_span(&widened).unwrap_or(widened);
+                    if widened == span {
+                        break Some(span);
+                    }
+                    span = widened;
+                }
+            })
+            .collect();
+
+        spans.sort_by_key(|span| span.start);
+        let mut merged_spans: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+        for span in spans {
+            match merged_spans.last_mut() {
+                // We merge touching spans too, so that the formatter can fix
+                // the blank lines between them
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => merged_spans.push(span),
+            }
+        }
+        merged_spans
+    }
+
+    fn get_line_start(&self, byte_idx: usize) -> usize {
+        self.content[..byte_idx]
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+    }
+
+    fn get_line_end(&self, byte_idx: usize) -> usize {
+        // Some nodes end with their line break
+        if self.content[..byte_idx].ends_with('\n') {
+            return byte_idx;
+        }
+        self.content[byte_idx..]
+            .find('\n')
+            .map_or(self.content.len(), |index| byte_idx + index + 1)
+    }
+
+    /// This function wraps lines that are longer than the configured line width.
+    ///
+    /// On each line that's too long, we look for the outermost construct that
+    /// fits on the line and that we can break: argument and parameter lists,
+    /// arrays, dictionaries, enums, and boolean chains inside of parentheses or
+    /// brackets. We insert line breaks in it and run Topiary again, which
+    /// formats constructs that span multiple lines with one element per line.
+    /// We repeat this until no long line can be wrapped anymore.
+    ///
+    /// Boolean chains outside of brackets are left as-is, because breaking them
+    /// would require adding parentheses and changing the code structure.
     #[inline(always)]
-    fn reorder(&mut self) -> &mut Self {
-        if !self.config.reorder_code {
-            return self;
+    fn wrap_long_lines(&mut self) -> Result<&mut Self, FormatError> {
+        if self.config.line_width == 0 {
+            return Ok(self);
         }
 
-        self.tree = self.parser.parse(&self.content, Some(&self.tree)).unwrap();
-        match crate::reorder::reorder_gdscript_elements(&self.tree, &self.content) {
-            Ok(reordered) => {
-                self.content = reordered;
+        for _ in 0..MAX_LINE_WRAPPING_PASSES {
+            let long_rows: HashSet<usize> = self
+                .content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| get_display_width(line) > self.config.line_width)
+                .map(|(row, _)| row)
+                .collect();
+            if long_rows.is_empty() {
+                break;
             }
-            Err(e) => {
-                eprintln!(
-                    "Warning: Code reordering failed: {e}. Returning formatted code without reordering."
-                );
+
+            self.tree = self.parser.parse(&self.content, None).unwrap();
+            let mut wrapped_rows = HashSet::new();
+            let mut line_breaks = Vec::new();
+            collect_line_breaks(
+                self.tree.root_node(),
+                &self.content,
+                &long_rows,
+                &mut wrapped_rows,
+                &mut line_breaks,
+            );
+            if line_breaks.is_empty() {
+                break;
             }
-        };
-        self
+
+            // We insert line breaks from the end of the file so that byte
+            // positions stay valid as we modify the content
+            line_breaks.sort_unstable();
+            line_breaks.dedup();
+            for byte_idx in line_breaks.into_iter().rev() {
+                self.content.insert(byte_idx, '\n');
+            }
+
+            self.tree = self.parser.parse(&self.content, None).unwrap();
+            self.format()?;
+        }
+
+        Ok(self)
+    }
+
+    /// This function puts back the original code in the regions where
+    /// formatting is turned off with `# fmt: off` and `# fmt: skip` comments.
+    /// It runs after post-processing so that no pass changes these regions,
+    /// and before reordering, which moves declarations without changing them.
+    #[inline(always)]
+    fn restore_skipped_regions(&mut self) -> Result<&mut Self, FormatError> {
+        if self.skipped_regions.is_empty() {
+            return Ok(self);
+        }
+
+        self.tree = self.parser.parse(&self.content, None).unwrap();
+        let formatted_regions = directives::find_skipped_regions(&self.tree, &self.content);
+        self.content = directives::restore_skipped_regions(
+            &self.input_content,
+            &self.skipped_regions,
+            &self.content,
+            &formatted_regions,
+        )
+        .map_err(FormatError::Divergence)?;
+        self.tree = self.parser.parse(&self.content, None).unwrap();
+        Ok(self)
+    }
+
+    #[inline(always)]
+    fn reorder(&mut self) -> Result<&mut Self, FormatError> {
+        if !self.config.reorder_code {
+            return Ok(self);
+        }
+
+        self.tree = self.parser.parse(&self.content, Some(&self.tree)).unwrap();
+        self.content = crate::reorder::reorder_gdscript_elements(&self.tree, &self.content)?;
+        Ok(self)
     }
 
     /// This function runs over the content before going through topiary.
@@ -143,13 +440,27 @@ impl Formatter {
 
     /// Finishes formatting and returns the resulting file content.
     #[inline(always)]
-    fn finish(mut self) -> Result<String, Box<dyn std::error::Error>> {
-        if self.config.safe {
+    fn finish(mut self) -> Result<String, FormatError> {
+        if self.config.safe || self.config.strict_safe {
             self.tree = self.parser.parse(&self.content, None).unwrap();
 
             let output_tree = GdTree::from_ts_tree(&self.tree, self.content.as_bytes());
-            if self.input_tree != output_tree {
-                return Err("Code structure has changed after formatting".into());
+            let compare_text = self.config.strict_safe;
+            let difference = if self.config.reorder_code {
+                self.input_tree
+                    .find_reordered_difference(&output_tree, compare_text)
+            } else {
+                self.input_tree.find_difference(&output_tree, compare_text)
+            };
+            if let Some(difference) = difference {
+                // Code with syntax errors often changes structure, as the
+                // parser recovers from errors differently after formatting
+                if !self.input_syntax_errors.is_empty() {
+                    return Err(FormatError::Parse(self.input_syntax_errors));
+                }
+                return Err(FormatError::Divergence(
+                    difference.to_report(&self.input_content, &self.content),
+                ));
             }
         }
 
@@ -444,6 +755,23 @@ impl Formatter {
     }
 }
 
+/// Node kinds of comments, which the reorder module moves along with the
+/// declarations they belong to.
+const COMMENT_KINDS: &[&str] = &["comment", "region_start", "region_end"];
+
+/// Names of the annotations that apply to the whole script. The reorder
+/// module keeps them at the top of the file.
+const CLASS_ANNOTATIONS: &[&str] = &["tool", "icon", "static_unload"];
+
+/// Node kinds of strings. Strict safe mode compares their text exactly, while
+/// it ignores whitespace in the text of other nodes.
+const STRING_KINDS: &[&str] = &["string", "string_name", "node_path"];
+
+/// Tokens that the formatter adds or removes on purpose, like trailing commas
+/// in multiline arrays and semicolons at the end of lines. Strict safe mode
+/// ignores them.
+const IGNORED_TOKENS: &[&str] = &[",", ";"];
+
 /// A syntax tree of the source code.
 struct GdTree {
     nodes: Vec<GdTreeNode>,
@@ -462,6 +790,8 @@ impl GdTree {
             grammar_id: ts_root.grammar_id(),
             grammar_name: ts_root.grammar_name(),
             text: None,
+            tokens: Vec::new(),
+            range: ts_root.byte_range(),
             children: Vec::new(),
         };
         nodes.push(root);
@@ -472,14 +802,21 @@ impl GdTree {
         while let Some((parent_ts_node, parent_node_id)) = queue.pop_front() {
             let ts_children = parent_ts_node.children(&mut cursor);
             for ts_child in ts_children {
-                // Skip anonymous nodes
+                // Anonymous nodes are keywords, operators, and punctuation,
+                // we only keep their text for strict safe mode
                 if !ts_child.is_named() {
+                    if !IGNORED_TOKENS.contains(&ts_child.kind()) {
+                        nodes[parent_node_id].tokens.push(ts_child.kind());
+                    }
                     continue;
                 }
 
                 // Get node's text in the source code (e.g. variable's name)
-                // None if this node is not a leaf node
-                let text = if ts_child.child(0).is_none() {
+                // None if this node is not a leaf node, a comment, or a string
+                let has_text = ts_child.child(0).is_none()
+                    || COMMENT_KINDS.contains(&ts_child.kind())
+                    || STRING_KINDS.contains(&ts_child.kind());
+                let text = if has_text {
                     let range = ts_child.range();
                     Some(
                         str::from_utf8(&source[range.start_byte..range.end_byte])
@@ -496,6 +833,8 @@ impl GdTree {
                     grammar_id: ts_child.grammar_id(),
                     grammar_name: ts_child.grammar_name(),
                     text,
+                    tokens: Vec::new(),
+                    range: ts_child.byte_range(),
                     children: Vec::new(),
                 };
                 nodes.push(child);
@@ -603,6 +942,10 @@ impl GdTree {
                     if annotations_to_move.is_empty() {
                         continue;
                     }
+                    // We collected the annotations from the last to the first
+                    let annotations_range =
+                        self.nodes[*annotations_to_move.last().unwrap()].range.start
+                            ..self.nodes[annotations_to_move[0]].range.end;
 
                     let mut annotations_node_exists = false;
 
@@ -622,6 +965,8 @@ impl GdTree {
                                 grammar_id: annotations_grammar_id,
                                 grammar_name: "annotations",
                                 text: None,
+                                tokens: Vec::new(),
+                                range: annotations_range,
                                 children: Vec::new(),
                             };
                             let annotations_id = self.nodes.len();
@@ -629,7 +974,7 @@ impl GdTree {
                             (&mut self.nodes[annotations_id], annotations_id)
                         };
 
-                    for annotation_id in annotations_to_move {
+                    for &annotation_id in &annotations_to_move {
                         annotations_node.children.insert(0, annotation_id);
                     }
 
@@ -637,6 +982,10 @@ impl GdTree {
                         let variable_node = &mut self.nodes[child_id];
                         variable_node.children.insert(0, annotations_node_id);
                     }
+
+                    for annotation_id in annotations_to_move {
+                        self.nodes[annotation_id].parent_id = Some(annotations_node_id);
+                    }
                 }
             }
         }
@@ -645,43 +994,327 @@ impl GdTree {
 
 impl PartialEq for GdTree {
     fn eq(&self, other: &Self) -> bool {
-        let mut left_stack = Vec::new();
-        let mut right_stack = Vec::new();
+        self.find_difference(other, false).is_none()
+    }
+}
 
-        // Starting from root (0)
-        left_stack.push(0);
-        right_stack.push(0);
+impl GdTree {
+    /// Returns the first node, in the order of the code, where this tree and
+    /// `other` differ, or None if they have the same structure. With
+    /// `compare_text`, the nodes must also have the same text.
+    fn find_difference(&self, other: &Self, compare_text: bool) -> Option<TreeDifference> {
+        self.find_subtree_difference(0, other, 0, false, compare_text)
+    }
 
-        while let (Some(left_current_node_id), Some(right_current_node_id)) =
-            (left_stack.pop(), right_stack.pop())
+    /// Compares the subtree of `left_id` in this tree with the subtree of
+    /// `right_id` in `other` and returns the first difference between them.
+    /// Comment nodes are skipped when `ignore_comments` is true.
+    fn find_subtree_difference(
+        &self,
+        left_id: usize,
+        other: &Self,
+        right_id: usize,
+        ignore_comments: bool,
+        compare_text: bool,
+    ) -> Option<TreeDifference> {
+        let mut stack = Vec::new();
+        self.push_child_pairs(&mut stack, left_id, other, right_id, ignore_comments);
+
+        while let Some((left_parent_id, left_node_id, right_parent_id, right_node_id)) = stack.pop()
         {
-            let left_current_node = &self.nodes[left_current_node_id];
-            let right_current_node = &other.nodes[right_current_node_id];
-            if left_current_node.children.len() != right_current_node.children.len() {
-                // A different number of children means the syntax trees are different, so the code
-                // structure has changed.
+            match (left_node_id, right_node_id) {
+                (Some(left_node_id), Some(right_node_id))
+                    if self.nodes[left_node_id].grammar_id
+                        == other.nodes[right_node_id].grammar_id
+                        && (!compare_text
+                            || self.has_same_text(left_node_id, other, right_node_id)) =>
+                {
+                    self.push_child_pairs(
+                        &mut stack,
+                        left_node_id,
+                        other,
+                        right_node_id,
+                        ignore_comments,
+                    );
+                }
+                // A missing node means the parents have a different number of children, so the code
+                // structure has changed. Otherwise, the nodes have a different kind or text.
                 // NOTE: There's a valid case of change: an annotation above a variable may be wrapped
                 // on the same line as the variable, which turns the annotation into a child of the variable.
                 // We could ignore this specific case, but for now, we consider any change in structure
                 // as a potential issue.
-                return false;
+                _ => {
+                    return Some(TreeDifference::new(
+                        (self, left_parent_id, left_node_id),
+                        (other, right_parent_id, right_node_id),
+                    ));
+                }
             }
+        }
+        None
+    }
 
-            for (left_node_id, right_node_id) in left_current_node
-                .children
-                .iter()
-                .zip(right_current_node.children.iter())
-            {
-                let left_node = &self.nodes[*left_node_id];
-                let right_node = &other.nodes[*right_node_id];
-                if left_node.grammar_id != right_node.grammar_id {
-                    return false;
+    /// Pushes the children of `left_id` and `right_id` on the stack as pairs,
+    /// so that the first children get popped first. When one node has more
+    /// children than the other, the extra children are paired with None.
+    fn push_child_pairs(
+        &self,
+        stack: &mut Vec<(usize, Option<usize>, usize, Option<usize>)>,
+        left_id: usize,
+        other: &Self,
+        right_id: usize,
+        ignore_comments: bool,
+    ) {
+        let left_children = self.get_children(left_id, ignore_comments);
+        let right_children = other.get_children(right_id, ignore_comments);
+        for index in (0..left_children.len().max(right_children.len())).rev() {
+            stack.push((
+                left_id,
+                left_children.get(index).copied(),
+                right_id,
+                right_children.get(index).copied(),
+            ));
+        }
+    }
+
+    /// Compares this tree to the tree of the same code after reordering it.
+    ///
+    /// Reordering moves top-level declarations, so we match each declaration
+    /// with one of the same kind and name in `other`, in any order, and then
+    /// compare their subtrees. Annotations on their own line must stay above
+    /// the declaration they apply to, so we match them along with it.
+    /// Comments move along with the declarations and can end up at a
+    /// different depth in the tree, so instead of comparing their position,
+    /// we check that every comment is still in `other`.
+    fn find_reordered_difference(
+        &self,
+        other: &Self,
+        compare_text: bool,
+    ) -> Option<TreeDifference> {
+        let mut right_declarations = other.get_top_level_declarations();
+
+        for left_nodes in self.get_top_level_declarations() {
+            let left_id = *left_nodes.last().unwrap();
+            let left_node = &self.nodes[left_id];
+            let left_name = self.get_declaration_name(left_id);
+            let candidates: Vec<usize> = (0..right_declarations.len())
+                .filter(|&index| {
+                    let right_id = *right_declarations[index].last().unwrap();
+                    other.nodes[right_id].grammar_id == left_node.grammar_id
+                        && other.get_declaration_name(right_id) == left_name
+                })
+                .collect();
+
+            let mut first_difference = None;
+            let matching_index = candidates.into_iter().find(|&index| {
+                let difference = self.find_declaration_difference(
+                    &left_nodes,
+                    other,
+                    &right_declarations[index],
+                    compare_text,
+                );
+                let is_match = difference.is_none();
+                first_difference = first_difference.take().or(difference);
+                is_match
+            });
+            match matching_index {
+                Some(index) => {
+                    right_declarations.swap_remove(index);
+                }
+                // If a declaration with the same name changed, we report the
+                // difference inside of it. Otherwise, it's missing.
+                None => {
+                    return first_difference.or_else(|| {
+                        Some(TreeDifference::new(
+                            (self, 0, Some(left_nodes[0])),
+                            (other, 0, None),
+                        ))
+                    });
                 }
-                left_stack.push(*left_node_id);
-                right_stack.push(*right_node_id);
             }
         }
-        true
+
+        if let Some(right_id) = right_declarations
+            .into_iter()
+            .map(|right_nodes| right_nodes[0])
+            .min_by_key(|&right_id| other.nodes[right_id].range.start)
+        {
+            return Some(TreeDifference::new(
+                (self, 0, None),
+                (other, 0, Some(right_id)),
+            ));
+        }
+
+        // Every comment of the input must still be in the output, as many
+        // times as in the input
+        let mut right_comments = other.count_comments();
+        let mut left_comment_ids: Vec<usize> = (0..self.nodes.len())
+            .filter(|&node_id| self.nodes[node_id].text.is_some() && self.is_comment(node_id))
+            .collect();
+        left_comment_ids.sort_by_key(|&node_id| self.nodes[node_id].range.start);
+        for node_id in left_comment_ids {
+            let text = self.nodes[node_id]
+                .text
+                .as_deref()
+                .unwrap_or_default()
+                .trim();
+            match right_comments.get_mut(text) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => {
+                    let parent_id = self.nodes[node_id].parent_id.unwrap_or(0);
+                    return Some(TreeDifference::new(
+                        (self, parent_id, Some(node_id)),
+                        (other, 0, None),
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the top-level declarations of the tree, skipping comments. Each
+    /// declaration comes with the annotations on their own line above it, like
+    /// `@rpc` above a function, followed by the declaration itself. Class
+    /// annotations like `@tool` apply to the whole script, so each of them is
+    /// a declaration of its own.
+    fn get_top_level_declarations(&self) -> Vec<Vec<usize>> {
+        let mut declarations = Vec::new();
+        let mut annotations = Vec::new();
+        for node_id in self.get_children(0, true) {
+            let is_declaration_annotation = self.nodes[node_id].grammar_name == "annotation"
+                && !self
+                    .get_declaration_name(node_id)
+                    .is_some_and(|name| CLASS_ANNOTATIONS.contains(&name));
+            annotations.push(node_id);
+            if !is_declaration_annotation {
+                declarations.push(std::mem::take(&mut annotations));
+            }
+        }
+        // Annotations at the end of the file don't apply to any declaration
+        declarations.extend(annotations.into_iter().map(|node_id| vec![node_id]));
+        declarations
+    }
+
+    /// Compares two declarations from `get_top_level_declarations()`, node by
+    /// node, and returns the first difference between them. Annotations must
+    /// have the same name, even when we don't compare text.
+    fn find_declaration_difference(
+        &self,
+        left_nodes: &[usize],
+        other: &Self,
+        right_nodes: &[usize],
+        compare_text: bool,
+    ) -> Option<TreeDifference> {
+        for index in 0..left_nodes.len().max(right_nodes.len()) {
+            let (Some(&left_id), Some(&right_id)) = (left_nodes.get(index), right_nodes.get(index))
+            else {
+                return Some(TreeDifference::new(
+                    (self, 0, left_nodes.get(index).copied()),
+                    (other, 0, right_nodes.get(index).copied()),
+                ));
+            };
+            if self.nodes[left_id].grammar_id != other.nodes[right_id].grammar_id
+                || self.get_declaration_name(left_id) != other.get_declaration_name(right_id)
+            {
+                return Some(TreeDifference::new(
+                    (self, 0, Some(left_id)),
+                    (other, 0, Some(right_id)),
+                ));
+            }
+            let difference =
+                self.find_subtree_difference(left_id, other, right_id, true, compare_text);
+            if difference.is_some() {
+                return difference;
+            }
+        }
+        None
+    }
+
+    /// Returns true if the nodes have the same text and the same anonymous
+    /// tokens, like operators and keywords. We ignore whitespace, except in
+    /// strings.
+    fn has_same_text(&self, left_id: usize, other: &Self, right_id: usize) -> bool {
+        let left_node = &self.nodes[left_id];
+        let right_node = &other.nodes[right_id];
+        if left_node.tokens != right_node.tokens {
+            return false;
+        }
+        match (&left_node.text, &right_node.text) {
+            (Some(left_text), Some(right_text))
+                if STRING_KINDS.contains(&left_node.grammar_name) =>
+            {
+                left_text == right_text
+            }
+            (Some(left_text), Some(right_text)) => {
+                let is_not_whitespace = |ch: &char| !ch.is_whitespace();
+                left_text
+                    .chars()
+                    .filter(is_not_whitespace)
+                    .eq(right_text.chars().filter(is_not_whitespace))
+            }
+            (left_text, right_text) => left_text.is_none() && right_text.is_none(),
+        }
+    }
+
+    fn is_comment(&self, node_id: usize) -> bool {
+        COMMENT_KINDS.contains(&self.nodes[node_id].grammar_name)
+    }
+
+    fn get_children(&self, node_id: usize, ignore_comments: bool) -> Vec<usize> {
+        self.nodes[node_id]
+            .children
+            .iter()
+            .copied()
+            .filter(|&child_id| !ignore_comments || !self.is_comment(child_id))
+            .collect()
+    }
+
+    /// Returns the name of a declaration like a function, variable, or class,
+    /// or the name of an annotation.
+    fn get_declaration_name(&self, node_id: usize) -> Option<&str> {
+        self.nodes[node_id]
+            .children
+            .iter()
+            .map(|&child_id| &self.nodes[child_id])
+            .find(|child| matches!(child.grammar_name, "name" | "identifier"))
+            .and_then(|child| child.text.as_deref())
+    }
+
+    /// Returns the kind of the node followed by its name if it has one, for
+    /// example `function_definition(name=_ready)`.
+    fn describe_node(&self, node_id: usize) -> String {
+        let kind = self.nodes[node_id].grammar_name;
+        match self.get_declaration_name(node_id) {
+            Some(name) => format!("{kind}(name={name})"),
+            None => kind.to_string(),
+        }
+    }
+
+    /// Returns the path from the root of the tree to the node, for example
+    /// `source > function_definition(name=_ready) > body`.
+    fn get_path(&self, node_id: usize) -> String {
+        let mut path = vec![self.describe_node(node_id)];
+        let mut current_id = node_id;
+        while let Some(parent_id) = self.nodes[current_id].parent_id {
+            path.push(self.describe_node(parent_id));
+            current_id = parent_id;
+        }
+        path.reverse();
+        path.join(" > ")
+    }
+
+    /// Returns the number of times each comment appears in the tree. We trim
+    /// the comments as the formatter removes trailing whitespace.
+    fn count_comments(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for (node_id, node) in self.nodes.iter().enumerate() {
+            if self.is_comment(node_id)
+                && let Some(text) = &node.text
+            {
+                *counts.entry(text.trim()).or_default() += 1;
+            }
+        }
+        counts
     }
 }
 
@@ -690,9 +1323,207 @@ struct GdTreeNode {
     grammar_id: u16,
     grammar_name: &'static str,
     text: Option<String>,
+    /// Text of the anonymous children of the node, like operators and
+    /// keywords, except for the ones in `IGNORED_TOKENS`.
+    tokens: Vec<&'static str>,
+    /// Byte range of the node in the code the tree was built from.
+    range: Range<usize>,
     children: Vec<usize>,
 }
 
+/// Maximum number of lines of code we show for each side of a difference
+/// found in safe mode.
+const MAX_EXCERPT_LINES: usize = 5;
+
+/// The first place where the syntax trees of the code before and after
+/// formatting differ.
+#[derive(Debug)]
+struct TreeDifference {
+    /// Path from the root of the tree to the node that differs.
+    path: String,
+    /// Kinds of the nodes that differ, or None when a node is missing on one
+    /// side.
+    input_kind: Option<&'static str>,
+    output_kind: Option<&'static str>,
+    /// Byte ranges of the nodes that differ. When a node is missing, this is
+    /// the range of the parent node that should contain it.
+    input_range: Range<usize>,
+    output_range: Range<usize>,
+}
+
+impl TreeDifference {
+    /// Creates a difference from a node of each tree, given as the tree, the
+    /// id of the parent node, and the id of the node if it exists.
+    fn new(
+        (input_tree, input_parent_id, input_node_id): (&GdTree, usize, Option<usize>),
+        (output_tree, output_parent_id, output_node_id): (&GdTree, usize, Option<usize>),
+    ) -> Self {
+        let path = match (input_node_id, output_node_id) {
+            (Some(node_id), _) => input_tree.get_path(node_id),
+            (None, Some(node_id)) => output_tree.get_path(node_id),
+            (None, None) => input_tree.get_path(input_parent_id),
+        };
+        let input_node = &input_tree.nodes[input_node_id.unwrap_or(input_parent_id)];
+        let output_node = &output_tree.nodes[output_node_id.unwrap_or(output_parent_id)];
+        Self {
+            path,
+            input_kind: input_node_id.map(|_| input_node.grammar_name),
+            output_kind: output_node_id.map(|_| output_node.grammar_name),
+            input_range: input_node.range.clone(),
+            output_range: output_node.range.clone(),
+        }
+    }
+
+    /// Returns a report of the difference with an excerpt of the input and
+    /// output code.
+    fn to_report(&self, input: &str, output: &str) -> String {
+        format!(
+            "Code structure has changed after formatting at {}\n{}\n{}",
+            self.path,
+            describe_side("Input", self.input_kind, &self.input_range, input),
+            describe_side("Output", self.output_kind, &self.output_range, output),
+        )
+    }
+}
+
+/// Describes one side of a tree difference: the kind of the node, its line
+/// and byte range, and the first lines of its code.
+fn describe_side(label: &str, kind: Option<&str>, range: &Range<usize>, code: &str) -> String {
+    let line_start = code[..range.start].rfind('\n').map_or(0, |index| index + 1);
+    let first_line = code[..range.start].matches('\n').count() + 1;
+    let kind = match kind {
+        Some(kind) => kind.to_string(),
+        None => "missing, showing the parent node".to_string(),
+    };
+
+    let mut description = format!(
+        "{label}: {kind} at line {first_line}, bytes {}..{}",
+        range.start, range.end
+    );
+    let excerpt_lines: Vec<&str> = code[line_start..range.end].lines().collect();
+    for (index, line) in excerpt_lines.iter().take(MAX_EXCERPT_LINES).enumerate() {
+        description.push_str(&format!("\n  {:>4} | {}", first_line + index, line));
+    }
+    if excerpt_lines.len() > MAX_EXCERPT_LINES {
+        description.push_str("\n       | ...");
+    }
+    description
+}
+
+/// Returns the width of a line as displayed in a code editor, counting tabs as
+/// 4 columns like the linter's max-line-length rule.
+fn get_display_width(line: &str) -> usize {
+    line.chars().fold(
+        0,
+        |width, ch| if ch == '\t' { width + 4 } else { width + 1 },
+    )
+}
+
+/// Walks the tree from `node` and collects the byte positions where to insert
+/// line breaks to wrap the rows in `long_rows`. We wrap at most one construct
+/// per row, the outermost one, as wrapping it changes the layout of the
+/// constructs nested inside of it.
+fn collect_line_breaks(
+    node: Node,
+    source: &str,
+    long_rows: &HashSet<usize>,
+    wrapped_rows: &mut HashSet<usize>,
+    line_breaks: &mut Vec<usize>,
+) {
+    let row = node.start_position().row;
+    if long_rows.contains(&row) && !wrapped_rows.contains(&row) && node.end_position().row == row {
+        let node_line_breaks = get_line_breaks(node, source);
+        if !node_line_breaks.is_empty() {
+            line_breaks.extend(node_line_breaks);
+            wrapped_rows.insert(row);
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_line_breaks(child, source, long_rows, wrapped_rows, line_breaks);
+    }
+}
+
+/// Returns the byte positions where to insert line breaks to wrap `node`, or
+/// an empty list if we can't wrap this kind of node.
+fn get_line_breaks(node: Node, source: &str) -> Vec<usize> {
+    let mut line_breaks = Vec::new();
+    match node.kind() {
+        // Breaking the line after the opening bracket is enough: Topiary puts
+        // each element on its own line when the node spans multiple lines
+        "arguments" | "parameters" | "array" | "dictionary" | "enumerator_list" => {
+            if node.named_child_count() > 0
+                && let Some(opening_bracket) = node.child(0)
+            {
+                line_breaks.push(opening_bracket.end_byte());
+            }
+        }
+        "parenthesized_expression" => {
+            if let Some(expression) = node.named_child(0)
+                && let Some(operator) = get_boolean_operator(expression, source)
+                && let (Some(opening_parenthesis), Some(closing_parenthesis)) =
+                    (node.child(0), node.child(node.child_count() - 1))
+            {
+                line_breaks.push(opening_parenthesis.end_byte());
+                collect_boolean_operator_breaks(expression, operator, source, &mut line_breaks);
+                line_breaks.push(closing_parenthesis.start_byte());
+            }
+        }
+        // Line breaks are allowed anywhere inside of brackets in GDScript, so
+        // we can break boolean chains that are direct elements of a list
+        "binary_operator" => {
+            if node
+                .parent()
+                .is_some_and(|parent| matches!(parent.kind(), "arguments" | "array"))
+                && let Some(operator) = get_boolean_operator(node, source)
+            {
+                collect_boolean_operator_breaks(node, operator, source, &mut line_breaks);
+            }
+        }
+        _ => {}
+    }
+    line_breaks
+}
+
+/// If `node` is a boolean operation (and, or), returns its operator normalized
+/// to the keyword form.
+fn get_boolean_operator(node: Node, source: &str) -> Option<&'static str> {
+    if node.kind() != "binary_operator" {
+        return None;
+    }
+    let operator = node.child_by_field_name("op")?;
+    match &source[operator.byte_range()] {
+        "and" | "&&" => Some("and"),
+        "or" | "||" => Some("or"),
+        _ => None,
+    }
+}
+
+/// Collects the positions before each operator of a chain of boolean
+/// operations using the same operator, like `a and b and c`. Operations with a
+/// different operator are nested constructs that we leave for later passes.
+fn collect_boolean_operator_breaks(
+    node: Node,
+    operator: &str,
+    source: &str,
+    line_breaks: &mut Vec<usize>,
+) {
+    if get_boolean_operator(node, source) != Some(operator) {
+        return;
+    }
+    if let Some(left) = node.child_by_field_name("left") {
+        collect_boolean_operator_breaks(left, operator, source, line_breaks);
+    }
+    if let Some(operator_node) = node.child_by_field_name("op") {
+        line_breaks.push(operator_node.start_byte());
+    }
+    if let Some(right) = node.child_by_field_name("right") {
+        collect_boolean_operator_breaks(right, operator, source, line_breaks);
+    }
+}
+
 /// Calculates end position of the `slice` counting from `start`
 fn calculate_end_position(mut start: Point, slice: &str) -> Point {
     for b in slice.as_bytes() {
@@ -705,3 +1536,101 @@ fn calculate_end_position(mut start: Point, slice: &str) -> Point {
     }
     start
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_tree(source: &str) -> GdTree {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        GdTree::from_ts_tree(&tree, source.as_bytes())
+    }
+
+    #[test]
+    fn test_reorder_error() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+            .unwrap();
+        // A tree that doesn't match the content makes reading the text of the
+        // declarations fail, as the variable ends in the middle of a character
+        let tree = parser.parse("var ab = 1\n", None).unwrap();
+        let error = crate::reorder::reorder_gdscript_elements(&tree, "var a = 1é\n").unwrap_err();
+        assert!(matches!(error, FormatError::Reorder(_)));
+        assert_eq!(error.exit_code(), 7);
+    }
+
+    #[test]
+    fn test_compare_reordered_trees() {
+        let input = parse_tree("func b():\n\tpass\n\n\n# Comment\nfunc a():\n\treturn 1\n");
+        let reordered = parse_tree("# Comment\nfunc a():\n\treturn 1\n\n\nfunc b():\n\tpass\n");
+        assert!(input.find_reordered_difference(&reordered, false).is_none());
+        assert!(input != reordered);
+
+        let missing_comment = parse_tree("func a():\n\treturn 1\n\n\nfunc b():\n\tpass\n");
+        let difference = input
+            .find_reordered_difference(&missing_comment, false)
+            .unwrap();
+        assert!(difference.path.ends_with("comment"));
+        assert_eq!(difference.output_kind, None);
+
+        let swapped_bodies =
+            parse_tree("# Comment\nfunc a():\n\tpass\n\n\nfunc b():\n\treturn 1\n");
+        let difference = input
+            .find_reordered_difference(&swapped_bodies, false)
+            .unwrap();
+        assert_eq!(
+            difference.path,
+            "source > function_definition(name=b) > body > pass_statement"
+        );
+
+        // Annotations on their own line must stay with their function
+        let input = parse_tree("@rpc\nfunc b():\n\tpass\n\n\nfunc a():\n\tpass\n");
+        let reordered = parse_tree("func a():\n\tpass\n\n\n@rpc\nfunc b():\n\tpass\n");
+        assert!(input.find_reordered_difference(&reordered, false).is_none());
+
+        let moved_annotation = parse_tree("@rpc\nfunc a():\n\tpass\n\n\nfunc b():\n\tpass\n");
+        let difference = input
+            .find_reordered_difference(&moved_annotation, false)
+            .unwrap();
+        assert_eq!(difference.path, "source > annotation(name=rpc)");
+        assert_eq!(difference.output_kind, Some("function_definition"));
+    }
+
+    #[test]
+    fn test_report_tree_difference() {
+        let input = "func a():\n\tpass\n";
+        let output = "func a():\n\treturn\n";
+        let difference = parse_tree(input)
+            .find_difference(&parse_tree(output), false)
+            .unwrap();
+        assert_eq!(
+            difference.to_report(input, output),
+            "Code structure has changed after formatting at source > function_definition(name=a) > body > pass_statement\n\
+             Input: pass_statement at line 2, bytes 11..15\n     2 | \tpass\n\
+             Output: return_statement at line 2, bytes 11..17\n     2 | \treturn"
+        );
+    }
+
+    #[test]
+    fn test_compare_tree_text() {
+        let input = parse_tree("var speed = base_speed + 10\nvar label = \"a  b\"\n");
+        let reformatted = parse_tree("var speed = base_speed  +  10;\nvar label = \"a  b\"\n");
+        assert!(input.find_difference(&reformatted, true).is_none());
+
+        for changed_code in [
+            "var speed = base_speed - 10\nvar label = \"a  b\"\n",
+            "var speed = base_sped + 10\nvar label = \"a  b\"\n",
+            "var speed = base_speed + 100\nvar label = \"a  b\"\n",
+            "var speed = base_speed + 10\nvar label = \"a b\"\n",
+        ] {
+            let output = parse_tree(changed_code);
+            assert!(input.find_difference(&output, false).is_none());
+            assert!(input.find_difference(&output, true).is_some());
+        }
+    }
+}
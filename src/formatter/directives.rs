@@ -0,0 +1,207 @@
+//! This module handles the comments that turn off formatting for parts of a
+//! file:
+//!
+//! - `# fmt: off` and `# fmt: on` on their own lines leave all the lines
+//!   between them unformatted. Without a matching `# fmt: on`, formatting stays
+//!   off until the end of the file.
+//! - `# fmt: skip` at the end of a statement leaves this statement unformatted.
+//!
+//! We format the whole file as usual, then find the skipped regions in the
+//! formatted code using the directive comments and put back the original code.
+//! Only the indentation of the original code changes, to match the block it's
+//! in after formatting.
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+/// A directive comment that controls formatting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Directive {
+    Off,
+    On,
+    Skip,
+}
+
+/// A range of lines to leave unformatted.
+#[derive(Debug, Clone)]
+pub(super) struct SkippedRegion {
+    /// Rows of the region, starting at 0. The end is excluded.
+    rows: Range<usize>,
+    /// Rows of the region along with the `# fmt: off` and `# fmt: on`
+    /// comments around it, if any.
+    pub(super) directive_rows: Range<usize>,
+    /// Indentation of the line the region is anchored to: the `# fmt: off`
+    /// comment or the first line of the skipped statement.
+    indent: String,
+}
+
+/// Returns the regions that must not be formatted, in the order they appear in
+/// the code.
+pub(super) fn find_skipped_regions(tree: &Tree, content: &str) -> Vec<SkippedRegion> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut comments = Vec::new();
+    collect_comments(tree.root_node(), &mut comments);
+
+    let mut regions = Vec::new();
+    // Row of the `# fmt: off` comment that starts the current region, if any
+    let mut off_row = None;
+    for comment in comments {
+        let Ok(text) = comment.utf8_text(content.as_bytes()) else {
+            continue;
+        };
+        let Some(directive) = parse_directive(text) else {
+            continue;
+        };
+
+        let row = comment.start_position().row;
+        let line = lines.get(row).copied().unwrap_or_default();
+        let is_on_own_line = line[..comment.start_position().column].trim().is_empty();
+        match directive {
+            Directive::Off if is_on_own_line && off_row.is_none() => off_row = Some(row),
+            Directive::On if is_on_own_line => {
+                if let Some(start_row) = off_row.take() {
+                    regions.push(SkippedRegion {
+                        rows: start_row + 1..row,
+                        directive_rows: start_row..row + 1,
+                        indent: get_indent(lines[start_row]).to_string(),
+                    });
+                }
+            }
+            // Statements in a `# fmt: off` region are skipped already
+            Directive::Skip if !is_on_own_line && off_row.is_none() => {
+                // The comment follows the statement it applies to, which can
+                // span multiple lines
+                let start_row = comment
+                    .prev_named_sibling()
+                    .filter(|statement| statement.end_position().row == row)
+                    .map_or(row, |statement| statement.start_position().row);
+                regions.push(SkippedRegion {
+                    rows: start_row..row + 1,
+                    directive_rows: start_row..row + 1,
+                    indent: get_indent(lines[start_row]).to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start_row) = off_row {
+        regions.push(SkippedRegion {
+            rows: start_row + 1..lines.len(),
+            directive_rows: start_row..lines.len(),
+            indent: get_indent(lines[start_row]).to_string(),
+        });
+    }
+    regions
+}
+
+/// Replaces the skipped regions of the `formatted` code with the code from the
+/// `original` regions. The regions must come from `find_skipped_regions()`
+/// called on the original and formatted code.
+pub(super) fn restore_skipped_regions(
+    original: &str,
+    original_regions: &[SkippedRegion],
+    formatted: &str,
+    formatted_regions: &[SkippedRegion],
+) -> Result<String, String> {
+    if original_regions.len() != formatted_regions.len() {
+        return Err(format!(
+            "Found {} regions with formatting turned off in the input but {} after formatting",
+            original_regions.len(),
+            formatted_regions.len()
+        ));
+    }
+
+    let original_lines: Vec<&str> = original.lines().collect();
+    let mut output_lines: Vec<String> = formatted.lines().map(str::to_string).collect();
+    // We replace regions from the end of the file so that row numbers stay
+    // valid as we modify the content
+    for (original_region, formatted_region) in original_regions.iter().zip(formatted_regions).rev()
+    {
+        let restored_lines = original_lines[original_region.rows.clone()]
+            .iter()
+            .map(
+                |line| match line.strip_prefix(original_region.indent.as_str()) {
+                    Some(rest) if !line.trim().is_empty() => {
+                        format!("{}{}", formatted_region.indent, rest)
+                    }
+                    _ => line.to_string(),
+                },
+            );
+        output_lines.splice(formatted_region.rows.clone(), restored_lines);
+    }
+
+    let mut output = output_lines.join("\n");
+    if formatted.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+fn collect_comments<'a>(node: Node<'a>, comments: &mut Vec<Node<'a>>) {
+    if node.kind() == "comment" {
+        comments.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_comments(child, comments);
+    }
+}
+
+/// Parses a comment like `# fmt: off`. Returns None if the comment is not a
+/// formatting directive.
+fn parse_directive(comment: &str) -> Option<Directive> {
+    let content = comment.strip_prefix('#')?.trim();
+    let directive = content.strip_prefix("fmt:")?.trim();
+    match directive {
+        "off" => Some(Directive::Off),
+        "on" => Some(Directive::On),
+        "skip" => Some(Directive::Skip),
+        _ => None,
+    }
+}
+
+fn get_indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directive() {
+        assert_eq!(parse_directive("# fmt: off"), Some(Directive::Off));
+        assert_eq!(parse_directive("#fmt:on"), Some(Directive::On));
+        assert_eq!(parse_directive("#  fmt:  skip "), Some(Directive::Skip));
+        assert_eq!(parse_directive("## fmt: off"), None);
+        assert_eq!(parse_directive("# fmt: offline"), None);
+        assert_eq!(parse_directive("# format: off"), None);
+    }
+
+    #[test]
+    fn test_restore_skipped_regions() {
+        let original = "func _ready():\n  # fmt: off\n  var grid = [\n    1,0,\n    0,1]\n  # fmt: on\n  pass\n";
+        let formatted =
+            "func _ready():\n\t# fmt: off\n\tvar grid = [1, 0, 0, 1]\n\t# fmt: on\n\tpass\n";
+        let original_regions = [SkippedRegion {
+            rows: 2..5,
+            directive_rows: 1..6,
+            indent: "  ".to_string(),
+        }];
+        let formatted_regions = [SkippedRegion {
+            rows: 2..3,
+            directive_rows: 1..4,
+            indent: "\t".to_string(),
+        }];
+
+        let restored =
+            restore_skipped_regions(original, &original_regions, formatted, &formatted_regions)
+                .unwrap();
+        assert_eq!(
+            restored,
+            "func _ready():\n\t# fmt: off\n\tvar grid = [\n\t  1,0,\n\t  0,1]\n\t# fmt: on\n\tpass\n"
+        );
+    }
+}
@@ -1,4 +1,10 @@
+pub mod config;
+pub mod diff;
+pub mod error;
+pub mod files;
 pub mod formatter;
+pub mod lsp;
+pub mod migrate;
 pub mod reorder;
 pub mod linter;
 
@@ -8,6 +14,12 @@ pub struct FormatterConfig {
     pub use_spaces: bool,
     pub reorder_code: bool,
     pub safe: bool,
+    /// Safe mode that also compares the text of identifiers, literals,
+    /// operators, and strings. Enabling it also enables safe mode.
+    pub strict_safe: bool,
+    /// Maximum line width. The formatter wraps longer lines when possible. 0
+    /// disables wrapping.
+    pub line_width: usize,
 }
 
 impl Default for FormatterConfig {
@@ -17,6 +29,8 @@ impl Default for FormatterConfig {
             use_spaces: false,
             reorder_code: false,
             safe: false,
+            strict_safe: false,
+            line_width: 100,
         }
     }
 }
@@ -3,31 +3,64 @@ use std::path::PathBuf;
 use std::{fs, io::IsTerminal};
 use tree_sitter::{Node, Parser};
 
+pub mod baseline;
+pub mod fix;
 pub mod ignore_patterns;
 pub mod lib;
+pub mod metrics;
+pub mod output;
 pub mod regex_patterns;
 pub mod rule_config;
 pub mod rules;
+pub mod scope;
+pub mod static_typing;
 
 #[cfg(test)]
 mod tests;
 
-use ignore_patterns::{parse_ignore_patterns, should_ignore_rule};
-use rules::{ALL_RULES, Rule};
-
-#[derive(Debug, Clone, PartialEq)]
+use crate::config::{ConfigResolver, FormatOptions, LintOptions};
+use crate::error::LintError;
+use crate::files::find_gdscript_files;
+use crate::formatter::format_gdscript_with_config;
+use baseline::{Baseline, BaselineMode};
+use fix::{Fix, apply_fixes};
+use ignore_patterns::{Suppressions, UNUSED_SUPPRESSION_RULE, parse_ignore_patterns};
+use output::{FileIssues, OutputFormat};
+use rule_config::{RuleSettings, RuleSeverity};
+use rules::{ALL_RULES, OPT_IN_RULES, Rule, resolve_rule_alias};
+use scope::ScopeTree;
+
+/// Severity of lint issues, ordered from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LintSeverity {
-    Error,
+    Info,
     Warning,
+    Error,
+}
+
+impl LintSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintSeverity::Info => "info",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct LintIssue {
     pub line: usize,
     pub column: usize,
+    /// Position right after the end of the code the issue is about. Lines and
+    /// columns start at 1, like `line` and `column`.
+    pub end_line: usize,
+    pub end_column: usize,
     pub rule: String,
     pub severity: LintSeverity,
     pub message: String,
+    /// Edits that fix the issue automatically, if the rule supports it.
+    pub fix: Option<Fix>,
 }
 
 impl LintIssue {
@@ -41,20 +74,35 @@ impl LintIssue {
         Self {
             line,
             column,
+            end_line: line,
+            end_column: column,
             rule,
             severity,
             message,
+            fix: None,
         }
     }
 
+    /// Sets the end position of the issue, which defaults to its start.
+    pub fn with_end(mut self, end_line: usize, end_column: usize) -> Self {
+        self.end_line = end_line;
+        self.end_column = end_column;
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
     pub fn format(&self, file_path: &str) -> String {
-        let severity_str = match self.severity {
-            LintSeverity::Error => "error",
-            LintSeverity::Warning => "warning",
-        };
         format!(
             "{}:{}:{}:{}: {}",
-            file_path, self.line, self.rule, severity_str, self.message
+            file_path,
+            self.line,
+            self.rule,
+            self.severity.as_str(),
+            self.message
         )
     }
 }
@@ -63,13 +111,45 @@ impl LintIssue {
 pub struct LinterConfig {
     pub disabled_rules: HashSet<String>,
     pub max_line_length: usize,
+    /// Maximum number of lines of a function, including its signature.
+    pub max_function_length: usize,
+    /// Maximum number of lines of a file.
+    pub max_file_length: usize,
+    pub max_parameters: usize,
+    /// Maximum number of nested blocks in a function.
+    pub max_nesting_depth: usize,
+    /// Maximum number of return statements in a function.
+    pub max_returns: usize,
+    pub max_cyclomatic_complexity: usize,
+    /// Severity overrides and options of individual rules, by rule name.
+    pub rules: HashMap<String, RuleSettings>,
+}
+
+/// Options of the lint --fix command.
+#[derive(Debug, Clone, Default)]
+pub struct FixOptions {
+    /// Also apply the fixes that may change the behavior of the code.
+    pub unsafe_fixes: bool,
+    /// Format the code after applying fixes.
+    pub run_formatter: bool,
 }
 
+/// Maximum number of times we apply fixes and lint the code again. Each pass
+/// can make new fixes possible, like removing the last statement of a block.
+const MAX_FIX_PASSES: usize = 10;
+
 impl Default for LinterConfig {
     fn default() -> Self {
         Self {
             disabled_rules: HashSet::new(),
             max_line_length: 100,
+            max_function_length: 50,
+            max_file_length: 1000,
+            max_parameters: 10,
+            max_nesting_depth: 4,
+            max_returns: 6,
+            max_cyclomatic_complexity: 10,
+            rules: HashMap::new(),
         }
     }
 }
@@ -80,30 +160,57 @@ pub struct GDScriptLinter {
 }
 
 impl GDScriptLinter {
-    pub fn new(config: LinterConfig) -> Result<Self, String> {
+    pub fn new(config: LinterConfig) -> Result<Self, LintError> {
         let mut parser = Parser::new();
         parser
             .set_language(&tree_sitter_gdscript::LANGUAGE.into())
-            .map_err(|e| format!("Failed to set language: {}", e))?;
+            .map_err(|e| LintError::Parser(format!("Failed to set language: {}", e)))?;
 
         Ok(Self { config, parser })
     }
 
-    pub fn lint(&mut self, source_code: &str, _file_path: &str) -> Result<Vec<LintIssue>, String> {
+    /// Replaces the configuration used by the next calls to `lint()`.
+    pub fn set_config(&mut self, config: LinterConfig) {
+        self.config = config;
+    }
+
+    pub fn lint(
+        &mut self,
+        source_code: &str,
+        _file_path: &str,
+    ) -> Result<Vec<LintIssue>, LintError> {
         let tree = self
             .parser
             .parse(source_code, None)
-            .ok_or("Failed to parse GDScript code")?;
+            .ok_or_else(|| LintError::Parser("Failed to parse GDScript code".to_string()))?;
 
         let root_node = tree.root_node();
         let mut issues = Vec::new();
 
-        let ignore_map = parse_ignore_patterns(source_code);
+        let mut suppressions = parse_ignore_patterns(&tree, source_code);
 
+        let default_settings = RuleSettings::default();
         let mut checkers: Vec<Box<dyn Rule>> = Vec::new();
+        let mut enabled_rules: HashSet<&str> = HashSet::new();
         for current_rule in ALL_RULES {
-            if !self.config.disabled_rules.contains(current_rule.name) {
-                checkers.push((current_rule.create)(&self.config));
+            let settings = self
+                .config
+                .rules
+                .get(current_rule.name)
+                .unwrap_or(&default_settings);
+            let is_disabled = self.config.disabled_rules.contains(current_rule.name)
+                || settings.severity == Some(RuleSeverity::Off)
+                || (OPT_IN_RULES.contains(&current_rule.name)
+                    && !self.config.rules.contains_key(current_rule.name));
+            if !is_disabled {
+                let checker = (current_rule.create)(&self.config, settings).map_err(|message| {
+                    LintError::RuleSettings {
+                        rule: current_rule.name,
+                        message,
+                    }
+                })?;
+                checkers.push(checker);
+                enabled_rules.insert(current_rule.name);
             }
         }
 
@@ -128,13 +235,14 @@ impl GDScriptLinter {
         }
 
         // First we run the rules that only care about the source code. Then we
-        // visit each node in the AST, calling the relevant rules. Finally we
+        // visit each node in the AST, calling the relevant rules. Then we pass
+        // the scopes of the file to the rules that resolve names. Finally we
         // call the finalize method on each rule in case a rule needs to collect
         // state while visiting nodes and report issues at the end.
         for &current_index in &source_only_rules {
             let rule_issues = checkers[current_index].check_source(source_code);
             for issue in rule_issues {
-                if !should_ignore_rule(&ignore_map, issue.line, &issue.rule) {
+                if !suppressions.is_suppressed(issue.line, &issue.rule) {
                     issues.push(issue);
                 }
             }
@@ -145,17 +253,46 @@ impl GDScriptLinter {
             &mut checkers,
             &node_kind_map,
             &mut issues,
-            &ignore_map,
+            &mut suppressions,
         );
+        let scopes = ScopeTree::new(root_node, source_code);
+        for checker in checkers.iter_mut() {
+            let rule_issues = checker.check_scopes(&scopes, source_code);
+            for issue in rule_issues {
+                if !suppressions.is_suppressed(issue.line, &issue.rule) {
+                    issues.push(issue);
+                }
+            }
+        }
         for checker in checkers.iter_mut() {
             let rule_issues = checker.finalize(source_code);
             for issue in rule_issues {
-                if !should_ignore_rule(&ignore_map, issue.line, &issue.rule) {
+                if !suppressions.is_suppressed(issue.line, &issue.rule) {
                     issues.push(issue);
                 }
             }
         }
 
+        if enabled_rules.contains(UNUSED_SUPPRESSION_RULE) {
+            let is_rule_enabled = |rule: &str| enabled_rules.contains(resolve_rule_alias(rule));
+            issues.extend(suppressions.get_unused_issues(is_rule_enabled));
+        }
+
+        // Rules report issues with their default severity, which the
+        // configuration can override. Issues reported under an alias use the
+        // settings of their rule.
+        for issue in &mut issues {
+            if let Some(severity) = self
+                .config
+                .rules
+                .get(resolve_rule_alias(&issue.rule))
+                .and_then(|settings| settings.severity)
+                .and_then(RuleSeverity::to_lint_severity)
+            {
+                issue.severity = severity;
+            }
+        }
+
         // Sort issues by line number. Rules that run on the source code like
         // line length check will otherwise appear at the end.
         issues.sort_by(|a, b| a.line.cmp(&b.line).then(a.column.cmp(&b.column)));
@@ -163,129 +300,221 @@ impl GDScriptLinter {
         Ok(issues)
     }
 
+    /// Applies the fixes of the issues found in `source_code` and returns the
+    /// fixed code. We apply fixes that don't overlap, lint the result again,
+    /// and repeat until there is nothing left to fix.
+    pub fn fix(
+        &mut self,
+        source_code: &str,
+        file_path: &str,
+        unsafe_fixes: bool,
+    ) -> Result<String, LintError> {
+        let mut fixed_code = source_code.to_string();
+        for _ in 0..MAX_FIX_PASSES {
+            let issues = self.lint(&fixed_code, file_path)?;
+            match apply_fixes(&fixed_code, &issues, unsafe_fixes) {
+                Some(output) => fixed_code = output,
+                None => break,
+            }
+        }
+        Ok(fixed_code)
+    }
+
+    /// Lints the GDScript files found in `input_files` and prints the issues
+    /// in the requested format. Each file is linted with the settings from the
+    /// project configuration files that apply to it, with `cli_options`
+    /// applied on top. Returns the severity of the most severe issue found, or
+    /// None if there was no issue.
+    ///
+    /// When writing a baseline, we record the issues in the baseline file
+    /// instead of printing them and return None.
     pub fn lint_files(
         &mut self,
         input_files: Vec<PathBuf>,
-        pretty: bool,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        let gdscript_files: Vec<&PathBuf> = input_files
-            .iter()
-            .filter(|path| path.extension().map_or(false, |ext| ext == "gd"))
-            .collect();
+        cli_options: &LintOptions,
+        format: OutputFormat,
+        fix_options: Option<&FixOptions>,
+        baseline_mode: Option<&BaselineMode>,
+    ) -> Result<Option<LintSeverity>, Box<dyn std::error::Error>> {
+        let gdscript_files = find_gdscript_files(&input_files)?;
 
         if gdscript_files.is_empty() {
             eprintln!(
-                "Error: No GDScript files found in the arguments provided. Please provide at least one .gd file."
+                "Error: No GDScript files found in the arguments provided. Please provide at least one .gd file or a directory containing .gd files."
             );
             std::process::exit(1);
         }
 
-        let with_colors = std::io::stdout().is_terminal();
-
-        if pretty {
-            self.lint_files_pretty(&gdscript_files, with_colors)
-        } else {
-            self.lint_files_standard(&gdscript_files)
-        }
-    }
-
-    fn lint_files_pretty(
-        &mut self,
-        gdscript_files: &[&PathBuf],
-        with_colors: bool,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        use std::collections::HashMap;
-        let mut file_issues: HashMap<String, Vec<_>> = HashMap::new();
-        let mut has_issues = false;
+        let mut baseline = match baseline_mode {
+            Some(BaselineMode::Compare(path)) => Baseline::load(path)?,
+            _ => Baseline::default(),
+        };
 
+        let mut config_resolver = ConfigResolver::new();
+        let mut file_issues: Vec<FileIssues> = Vec::new();
+        let mut fixed_files_count = 0;
         for file_path in gdscript_files {
-            let source_code = fs::read_to_string(file_path).map_err(|error| {
+            self.config = config_resolver.linter_config(&file_path, cli_options)?;
+            let mut source_code = fs::read_to_string(&file_path).map_err(|error| {
                 format!("Failed to read file {}: {}", file_path.display(), error)
             })?;
 
-            let issues = self.lint(&source_code, &file_path.to_string_lossy())?;
+            if let Some(fix_options) = fix_options {
+                let mut fixed_code = self.fix(
+                    &source_code,
+                    &file_path.to_string_lossy(),
+                    fix_options.unsafe_fixes,
+                )?;
+                if fix_options.run_formatter {
+                    let formatter_config =
+                        config_resolver.formatter_config(&file_path, &FormatOptions::default())?;
+                    fixed_code = format_gdscript_with_config(&fixed_code, &formatter_config)
+                        .map_err(|error| {
+                            format!("Failed to format file {}: {}", file_path.display(), error)
+                        })?;
+                }
+                if fixed_code != source_code {
+                    fs::write(&file_path, &fixed_code).map_err(|error| {
+                        format!("Failed to write to file {}: {}", file_path.display(), error)
+                    })?;
+                    fixed_files_count += 1;
+                    source_code = fixed_code;
+                }
+            }
 
+            let file_path = file_path.to_string_lossy().to_string();
+            let mut issues = self.lint(&source_code, &file_path)?;
+            match baseline_mode {
+                Some(BaselineMode::Write(_)) => {
+                    baseline.add_issues(&file_path, &issues, &source_code);
+                    continue;
+                }
+                Some(BaselineMode::Compare(_)) => {
+                    issues = baseline.filter_issues(&file_path, issues, &source_code);
+                }
+                None => {}
+            }
             if !issues.is_empty() {
-                has_issues = true;
-                file_issues.insert(file_path.to_string_lossy().to_string(), issues);
+                file_issues.push((file_path, issues));
             }
         }
 
-        // Print pretty output grouped by file and line
-        let mut file_iter = file_issues.iter().peekable();
-        while let Some((file_path, issues)) = file_iter.next() {
-            let bold = if with_colors { "\x1b[1m" } else { "" };
-            let reset = if with_colors { "\x1b[0m" } else { "" };
-
-            println!("{}{}{}", bold, file_path, reset);
-
-            // Group issues by line number
-            let mut line_issues: HashMap<usize, Vec<_>> = HashMap::new();
-            for issue in issues {
-                line_issues.entry(issue.line).or_default().push(issue);
-            }
-
-            // Sort by line number and print
-            let mut sorted_lines: Vec<_> = line_issues.keys().collect();
-            sorted_lines.sort();
-
-            for (i, &line_num) in sorted_lines.iter().enumerate() {
-                if let Some(line_issues) = line_issues.get(&line_num) {
-                    println!("    {}:{}", file_path, line_num);
-                    for issue in line_issues {
-                        let (severity_str, severity_color) = match issue.severity {
-                            LintSeverity::Error => ("ERROR", "\x1b[31m"),  // Red
-                            LintSeverity::Warning => ("WARN", "\x1b[33m"), // Yellow
-                        };
-
-                        if with_colors {
-                            println!(
-                                "        {}{}\x1b[0m: `{}`",
-                                severity_color, severity_str, issue.rule
-                            );
-                        } else {
-                            println!("        {}: `{}`", severity_str, issue.rule);
-                        }
-                        println!("        {}", issue.message);
-                    }
+        if let Some(BaselineMode::Write(path)) = baseline_mode {
+            baseline.save(path)?;
+            eprintln!(
+                "Wrote {} issue{} to the baseline {}",
+                baseline.len(),
+                if baseline.len() == 1 { "" } else { "s" },
+                path.display()
+            );
+            return Ok(None);
+        }
 
-                    // Add newline between line groups (except for the last line group)
-                    if i < sorted_lines.len() - 1 {
-                        println!();
+        match format {
+            OutputFormat::Text => {
+                for (file_path, issues) in &file_issues {
+                    for issue in issues {
+                        println!("{}", issue.format(file_path));
                     }
                 }
             }
+            OutputFormat::Pretty => {
+                print_pretty(&file_issues, std::io::stdout().is_terminal());
+            }
+            OutputFormat::Json => println!("{}", output::format_json(&file_issues)),
+            OutputFormat::Sarif => println!("{}", output::format_sarif(&file_issues)),
+            OutputFormat::Github => print!("{}", output::format_github(&file_issues)),
+        }
 
-            // Add separator between files (except for the last file)
-            if file_iter.peek().is_some() {
-                println!("\n{}", "-".repeat(60));
-                println!();
+        // Stale entries don't make the linter fail, but we list them so the
+        // baseline gets updated as issues get fixed
+        if let Some(BaselineMode::Compare(path)) = baseline_mode {
+            let stale_entries = baseline.stale_entries();
+            for entry in &stale_entries {
+                eprintln!(
+                    "Stale baseline entry: {} {} issue{} not found anymore in {}",
+                    entry.count,
+                    entry.rule,
+                    if entry.count == 1 { "" } else { "s" },
+                    entry.file
+                );
+            }
+            if !stale_entries.is_empty() {
+                eprintln!(
+                    "Run the linter with --write-baseline {} to remove stale entries",
+                    path.display()
+                );
             }
         }
 
-        Ok(has_issues)
+        if fix_options.is_some() {
+            eprintln!(
+                "Fixed {} file{}",
+                fixed_files_count,
+                if fixed_files_count == 1 { "" } else { "s" }
+            );
+        }
+
+        Ok(file_issues
+            .iter()
+            .flat_map(|(_, issues)| issues.iter().map(|issue| issue.severity))
+            .max())
     }
+}
 
-    fn lint_files_standard(
-        &mut self,
-        gdscript_files: &[&PathBuf],
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        let mut has_issues = false;
+/// Prints issues grouped by file and line, for people reading the output in a
+/// terminal.
+fn print_pretty(file_issues: &[FileIssues], with_colors: bool) {
+    let mut file_iter = file_issues.iter().peekable();
+    while let Some((file_path, issues)) = file_iter.next() {
+        let bold = if with_colors { "\x1b[1m" } else { "" };
+        let reset = if with_colors { "\x1b[0m" } else { "" };
 
-        for file_path in gdscript_files {
-            let source_code = fs::read_to_string(file_path).map_err(|error| {
-                format!("Failed to read file {}: {}", file_path.display(), error)
-            })?;
+        println!("{}{}{}", bold, file_path, reset);
 
-            let issues = self.lint(&source_code, &file_path.to_string_lossy())?;
+        // Group issues by line number
+        let mut line_issues: HashMap<usize, Vec<_>> = HashMap::new();
+        for issue in issues {
+            line_issues.entry(issue.line).or_default().push(issue);
+        }
 
-            for issue in issues {
-                has_issues = true;
-                println!("{}", issue.format(&file_path.to_string_lossy()));
+        // Sort by line number and print
+        let mut sorted_lines: Vec<_> = line_issues.keys().collect();
+        sorted_lines.sort();
+
+        for (i, &line_num) in sorted_lines.iter().enumerate() {
+            if let Some(line_issues) = line_issues.get(&line_num) {
+                println!("    {}:{}", file_path, line_num);
+                for issue in line_issues {
+                    let (severity_str, severity_color) = match issue.severity {
+                        LintSeverity::Error => ("ERROR", "\x1b[31m"),  // Red
+                        LintSeverity::Warning => ("WARN", "\x1b[33m"), // Yellow
+                        LintSeverity::Info => ("INFO", "\x1b[36m"),    // Cyan
+                    };
+
+                    if with_colors {
+                        println!(
+                            "        {}{}\x1b[0m: `{}`",
+                            severity_color, severity_str, issue.rule
+                        );
+                    } else {
+                        println!("        {}: `{}`", severity_str, issue.rule);
+                    }
+                    println!("        {}", issue.message);
+                }
+
+                // Add newline between line groups (except for the last line group)
+                if i < sorted_lines.len() - 1 {
+                    println!();
+                }
             }
         }
 
-        Ok(has_issues)
+        // Add separator between files (except for the last file)
+        if file_iter.peek().is_some() {
+            println!("\n{}", "-".repeat(60));
+            println!();
+        }
     }
 }
 
@@ -297,13 +526,13 @@ fn visit_each_node(
     checkers: &mut [Box<dyn Rule>],
     node_kind_map: &HashMap<String, Vec<usize>>,
     issues: &mut Vec<LintIssue>,
-    ignore_map: &HashMap<usize, HashSet<String>>,
+    suppressions: &mut Suppressions,
 ) {
     if let Some(matching_rules) = node_kind_map.get(node.kind()) {
         for &rule_idx in matching_rules {
             let rule_issues = checkers[rule_idx].check_node(node, source_code);
             for issue in rule_issues {
-                if !should_ignore_rule(ignore_map, issue.line, &issue.rule) {
+                if !suppressions.is_suppressed(issue.line, &issue.rule) {
                     issues.push(issue);
                 }
             }
@@ -319,7 +548,7 @@ fn visit_each_node(
                 checkers,
                 node_kind_map,
                 issues,
-                ignore_map,
+                suppressions,
             );
             if !cursor.goto_next_sibling() {
                 break;
@@ -332,12 +561,12 @@ pub fn lint_gdscript_with_config(
     source_code: &str,
     file_path: &str,
     config: &LinterConfig,
-) -> Result<Vec<LintIssue>, String> {
+) -> Result<Vec<LintIssue>, LintError> {
     let mut linter = GDScriptLinter::new(config.clone())?;
     linter.lint(source_code, file_path)
 }
 
-pub fn lint_gdscript(source_code: &str, file_path: &str) -> Result<Vec<LintIssue>, String> {
+pub fn lint_gdscript(source_code: &str, file_path: &str) -> Result<Vec<LintIssue>, LintError> {
     let config = LinterConfig::default();
     lint_gdscript_with_config(source_code, file_path, &config)
 }
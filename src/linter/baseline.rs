@@ -0,0 +1,282 @@
+//! Baseline files let projects adopt the linter without fixing every existing
+//! issue first. `lint --write-baseline` records the issues found in the
+//! project, and `lint --baseline` only reports issues that are not in the
+//! baseline.
+//!
+//! We identify issues by file, rule, and a hash of the content of the line
+//! they're on rather than their line number, so that adding or removing code
+//! above an issue doesn't make it appear as a new one. When the same line
+//! content has the same issue several times in a file, we store the number of
+//! occurrences.
+//!
+//! Baseline entries that no longer match any issue are stale: the issue was
+//! fixed, so the entry can be removed by writing the baseline again.
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::LintIssue;
+
+/// Version of the baseline file format, to detect files written by
+/// incompatible versions of the linter.
+const BASELINE_VERSION: u32 = 1;
+
+/// What the linter does with a baseline file.
+#[derive(Debug, Clone)]
+pub enum BaselineMode {
+    /// Record all the issues found in the baseline file.
+    Write(PathBuf),
+    /// Only report the issues that are not in the baseline file.
+    Compare(PathBuf),
+}
+
+/// Identifies issues across changes to the code.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct BaselineKey {
+    file: String,
+    rule: String,
+    line_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineEntry {
+    file: String,
+    rule: String,
+    line_hash: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    issues: Vec<BaselineEntry>,
+}
+
+/// A stale baseline entry: an issue recorded in the baseline that the linter
+/// didn't find anymore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleEntry {
+    pub file: String,
+    pub rule: String,
+    /// Number of recorded occurrences that weren't found.
+    pub count: usize,
+}
+
+/// The issues recorded in a baseline. When comparing, each issue found in the
+/// baseline consumes one recorded occurrence.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    issues: BTreeMap<BaselineKey, usize>,
+    /// Files that were linted, used to only report stale entries for them.
+    linted_files: HashSet<String>,
+}
+
+impl Baseline {
+    /// Reads a baseline file written by `save()`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read baseline {}: {}", path.display(), error))?;
+        let file: BaselineFile = serde_json::from_str(&content)
+            .map_err(|error| format!("Failed to parse baseline {}: {}", path.display(), error))?;
+        if file.version != BASELINE_VERSION {
+            return Err(format!(
+                "Unsupported baseline version {} in {}, expected {}. Write the baseline again with --write-baseline",
+                file.version,
+                path.display(),
+                BASELINE_VERSION
+            ));
+        }
+
+        let mut baseline = Self::default();
+        for entry in file.issues {
+            let key = BaselineKey {
+                file: entry.file,
+                rule: entry.rule,
+                line_hash: entry.line_hash,
+            };
+            *baseline.issues.entry(key).or_default() += entry.count;
+        }
+        Ok(baseline)
+    }
+
+    /// Writes the baseline as JSON, with entries sorted by file so that the
+    /// file produces small diffs in version control.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            issues: self
+                .issues
+                .iter()
+                .map(|(key, &count)| BaselineEntry {
+                    file: key.file.clone(),
+                    rule: key.rule.clone(),
+                    line_hash: key.line_hash.clone(),
+                    count,
+                })
+                .collect(),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|error| format!("Failed to serialize baseline: {}", error))?;
+        fs::write(path, content + "\n")
+            .map_err(|error| format!("Failed to write baseline {}: {}", path.display(), error))
+    }
+
+    /// Returns the number of issues recorded in the baseline.
+    pub fn len(&self) -> usize {
+        self.issues.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records the issues found in a file.
+    pub fn add_issues(&mut self, file_path: &str, issues: &[LintIssue], source_code: &str) {
+        let lines: Vec<&str> = source_code.lines().collect();
+        for issue in issues {
+            *self
+                .issues
+                .entry(get_key(file_path, issue, &lines))
+                .or_default() += 1;
+        }
+    }
+
+    /// Returns the issues of a file that are not in the baseline.
+    pub fn filter_issues(
+        &mut self,
+        file_path: &str,
+        issues: Vec<LintIssue>,
+        source_code: &str,
+    ) -> Vec<LintIssue> {
+        self.linted_files.insert(normalize_path(file_path));
+        let lines: Vec<&str> = source_code.lines().collect();
+        issues
+            .into_iter()
+            .filter(
+                |issue| match self.issues.get_mut(&get_key(file_path, issue, &lines)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect()
+    }
+
+    /// Returns the entries that didn't match any issue, for the files passed
+    /// to `filter_issues()` and the files that don't exist anymore.
+    pub fn stale_entries(&self) -> Vec<StaleEntry> {
+        self.issues
+            .iter()
+            .filter(|(key, count)| {
+                **count > 0
+                    && (self.linted_files.contains(&key.file) || !Path::new(&key.file).exists())
+            })
+            .map(|(key, &count)| StaleEntry {
+                file: key.file.clone(),
+                rule: key.rule.clone(),
+                count,
+            })
+            .collect()
+    }
+}
+
+fn get_key(file_path: &str, issue: &LintIssue, lines: &[&str]) -> BaselineKey {
+    let line = lines
+        .get(issue.line.saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    BaselineKey {
+        file: normalize_path(file_path),
+        rule: issue.rule.clone(),
+        line_hash: hash_line(line),
+    }
+}
+
+/// Uses forward slashes so that baselines work on all platforms.
+fn normalize_path(file_path: &str) -> String {
+    let path = file_path.replace('\\', "/");
+    match path.strip_prefix("./") {
+        Some(rest) => rest.to_string(),
+        None => path,
+    }
+}
+
+/// Hashes the content of a line with the 64-bit FNV-1a algorithm. We ignore
+/// the indentation and trailing whitespace, which the formatter can change.
+/// We don't use the standard library's hasher as its output can change
+/// between Rust versions.
+fn hash_line(line: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in line.trim().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linter::LintSeverity;
+
+    fn create_issue(line: usize, rule: &str) -> LintIssue {
+        LintIssue::new(
+            line,
+            1,
+            rule.to_string(),
+            LintSeverity::Warning,
+            "Test message".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_baseline_filter_issues() {
+        let source_code = "func _ready():\n\tpass\n\tpass\n";
+        let mut baseline = Baseline::default();
+        baseline.add_issues(
+            "./scripts/player.gd",
+            &[
+                create_issue(2, "unnecessary-pass"),
+                create_issue(3, "unnecessary-pass"),
+                create_issue(1, "function-name"),
+            ],
+            source_code,
+        );
+        assert_eq!(baseline.len(), 3);
+
+        // Moving the code down and fixing the function name issue doesn't
+        // change the keys of the remaining issues
+        let new_source_code = "extends Node\n\nfunc _ready():\n\tpass\n\tpass\n\tpass\n";
+        let issues = baseline.filter_issues(
+            "scripts/player.gd",
+            vec![
+                create_issue(4, "unnecessary-pass"),
+                create_issue(5, "unnecessary-pass"),
+                create_issue(6, "unnecessary-pass"),
+            ],
+            new_source_code,
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 6);
+
+        assert_eq!(
+            baseline.stale_entries(),
+            vec![StaleEntry {
+                file: "scripts/player.gd".to_string(),
+                rule: "function-name".to_string(),
+                count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_hash_line() {
+        assert_eq!(hash_line(""), "cbf29ce484222325");
+        assert_eq!(hash_line("\tpass  "), hash_line("pass"));
+        assert_ne!(hash_line("pass"), hash_line("return"));
+    }
+}
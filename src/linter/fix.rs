@@ -0,0 +1,172 @@
+//! Automatic fixes for lint issues.
+//!
+//! Rules can attach a `Fix` to the issues they report, made of text edits on
+//! the source code. Fixes are either safe, meaning they never change what the
+//! code does, or unsafe, meaning they can in rare cases, like replacing
+//! `x == x` with `true` when `x` can be NaN. The linter only applies unsafe
+//! fixes when the user opts in.
+//!
+//! Applying fixes can make other fixes possible or change their positions, so
+//! the linter applies the fixes that don't overlap, parses and lints the code
+//! again, and repeats until there is nothing left to fix.
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use super::LintIssue;
+
+/// Replaces a range of bytes in the source code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// A set of edits that fixes a lint issue. The edits are applied together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub edits: Vec<TextEdit>,
+    /// If false, the fix may change the behavior of the code and is only
+    /// applied with --unsafe-fixes.
+    pub is_safe: bool,
+}
+
+impl TextEdit {
+    pub fn replace(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Replaces the text of a node.
+    pub fn replace_node(node: &Node, replacement: impl Into<String>) -> Self {
+        Self::replace(node.byte_range(), replacement)
+    }
+
+    pub fn insert(byte_idx: usize, text: impl Into<String>) -> Self {
+        Self::replace(byte_idx..byte_idx, text)
+    }
+
+    /// Removes a statement. If the statement is alone on its line, we remove
+    /// the whole line, including the indentation and line break.
+    pub fn delete_statement(node: &Node, source_code: &str) -> Self {
+        let range = node.byte_range();
+        let line_start = source_code[..range.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = source_code[range.end..]
+            .find('\n')
+            .map_or(source_code.len(), |index| range.end + index + 1);
+
+        let is_alone_on_line = source_code[line_start..range.start].trim().is_empty()
+            && source_code[range.end..line_end].trim().is_empty();
+        if is_alone_on_line {
+            Self::replace(line_start..line_end, "")
+        } else {
+            Self::replace(range, "")
+        }
+    }
+}
+
+impl Fix {
+    pub fn safe(edits: Vec<TextEdit>) -> Self {
+        Self {
+            edits,
+            is_safe: true,
+        }
+    }
+
+    pub fn unsafe_fix(edits: Vec<TextEdit>) -> Self {
+        Self {
+            edits,
+            is_safe: false,
+        }
+    }
+}
+
+/// Applies the fixes of `issues` that don't overlap with each other to
+/// `source_code`. Returns None if there was no fix to apply.
+pub fn apply_fixes(source_code: &str, issues: &[LintIssue], unsafe_fixes: bool) -> Option<String> {
+    let mut fixes: Vec<&Fix> = issues
+        .iter()
+        .filter_map(|issue| issue.fix.as_ref())
+        .filter(|fix| (fix.is_safe || unsafe_fixes) && !fix.edits.is_empty())
+        .collect();
+    fixes.sort_by_key(|fix| get_fix_range(fix).start);
+
+    // We keep the fixes in order, skipping any fix that overlaps with the
+    // previous one. The next pass picks it up if it still applies.
+    let mut edits: Vec<&TextEdit> = Vec::new();
+    let mut last_range: Option<Range<usize>> = None;
+    for fix in fixes {
+        let range = get_fix_range(fix);
+        // Two edits starting at the same position also conflict, even if one
+        // of them is an insertion
+        if last_range
+            .as_ref()
+            .is_some_and(|last| range.start < last.end || range.start == last.start)
+        {
+            continue;
+        }
+        edits.extend(&fix.edits);
+        last_range = Some(range);
+    }
+    if edits.is_empty() {
+        return None;
+    }
+
+    // We apply edits from the end of the file so that byte positions stay
+    // valid as we modify the code
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    let mut output = source_code.to_string();
+    for edit in edits.into_iter().rev() {
+        output.replace_range(edit.range.clone(), &edit.replacement);
+    }
+    Some(output)
+}
+
+/// Returns the range of source code covered by all the edits of a fix.
+fn get_fix_range(fix: &Fix) -> Range<usize> {
+    let start = fix.edits.iter().map(|edit| edit.range.start).min();
+    let end = fix.edits.iter().map(|edit| edit.range.end).max();
+    start.unwrap_or(0)..end.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linter::LintSeverity;
+
+    fn create_issue(fix: Fix) -> LintIssue {
+        LintIssue::new(
+            1,
+            1,
+            "test-rule".to_string(),
+            LintSeverity::Warning,
+            "Test message".to_string(),
+        )
+        .with_fix(fix)
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let source_code = "var a = b == b\n";
+        let issues = [
+            create_issue(Fix::safe(vec![TextEdit::insert(4, "_")])),
+            // Overlaps with the previous fix, so it's skipped
+            create_issue(Fix::safe(vec![TextEdit::replace(4..5, "c")])),
+            create_issue(Fix::unsafe_fix(vec![TextEdit::replace(8..14, "true")])),
+        ];
+
+        assert_eq!(
+            apply_fixes(source_code, &issues, false).unwrap(),
+            "var _a = b == b\n"
+        );
+        assert_eq!(
+            apply_fixes(source_code, &issues, true).unwrap(),
+            "var _a = true\n"
+        );
+        assert!(apply_fixes(source_code, &issues[2..], false).is_none());
+    }
+}
@@ -1,111 +1,465 @@
-use std::collections::{HashMap, HashSet};
+//! This module parses the comments that suppress lint issues:
+//!
+//! - `# gdlint-ignore rules` at the end of a statement suppresses issues on
+//!   all the lines of that statement.
+//! - `# gdlint-ignore-next-line rules` suppresses issues on all the lines of
+//!   the next statement. For statements with a body, like functions or loops,
+//!   it only applies to the lines before the body.
+//! - `# gdlint-disable rules` and `# gdlint-enable rules` suppress issues on
+//!   all the lines between them, or until the end of the file without a
+//!   matching `gdlint-enable` comment.
+//! - `# gdlint-ignore-function rules` directly above a `func` suppresses issues
+//!   in the whole function.
+//! - `# gdlint-ignore-file rules`, usually at the top of the file, suppresses
+//!   issues in the whole file.
+//!
+//! We find comments and the statements they apply to using the syntax tree,
+//! so that multiline statements can be suppressed as a whole.
+//!
+//! Rule names are separated by commas or spaces. Without rule names, a comment
+//! suppresses all rules.
+//!
+//! We keep track of the suppressions that were used so that the linter can
+//! report comments that don't suppress anything or that name unknown rules.
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use tree_sitter::{Node, Point, Tree};
+
+use super::rules::{ALL_RULES, RULE_ALIASES};
+use super::{LintIssue, LintSeverity};
+
+/// Name of the rule reporting unused and invalid suppression comments.
+pub const UNUSED_SUPPRESSION_RULE: &str = "unused-suppression";
+
+/// Nodes that contain statements. We use them to find the statement a
+/// suppression comment applies to.
+const BLOCK_KINDS: &[&str] = &["source", "body", "class_body", "match_body"];
+
+const FUNCTION_KINDS: &[&str] = &["function_definition", "constructor_definition"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Directive {
+    Ignore,
+    IgnoreNextLine,
+    IgnoreFunction,
+    IgnoreFile,
+    Disable,
+    Enable,
+}
 
-/// Represents an ignore directive for a specific line
+/// Directive keywords, with the longest first so that `gdlint-ignore` doesn't
+/// match the start of `gdlint-ignore-next-line`.
+const DIRECTIVES: &[(&str, Directive)] = &[
+    ("gdlint-ignore-next-line", Directive::IgnoreNextLine),
+    ("gdlint-ignore-function", Directive::IgnoreFunction),
+    ("gdlint-ignore-line", Directive::Ignore),
+    ("gdlint-ignore-file", Directive::IgnoreFile),
+    ("gdlint-ignore", Directive::Ignore),
+    ("gdlint-disable", Directive::Disable),
+    ("gdlint-enable", Directive::Enable),
+];
+
+/// Position of a suppression comment in the source code, to report issues
+/// about the comment. Lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CommentPosition {
+    line: usize,
+    column: usize,
+    end_column: usize,
+}
+
+/// Rules suppressed on a range of lines by one comment.
 #[derive(Debug, Clone)]
-pub struct IgnoreDirective {
-    /// The line number this directive applies to
-    pub target_line: usize,
-    /// Set of rule names to ignore on this line
-    pub ignored_rules: HashSet<String>,
-}
-
-/// Parse ignore comments from source code and return a map of line numbers to ignored rules
-pub fn parse_ignore_patterns(source_code: &str) -> HashMap<usize, HashSet<String>> {
-    let mut ignore_map: HashMap<usize, HashSet<String>> = HashMap::new();
-
-    for (line_idx, line) in source_code.lines().enumerate() {
-        let line_number = line_idx + 1;
-
-        // Look for ignore comments
-        if let Some(comment_start) = line.find('#') {
-            let comment = &line[comment_start..];
-
-            // Check for gdlint-ignore patterns
-            if let Some(rules) = parse_ignore_comment(comment) {
-                // Check if this is a "next-line" directive
-                if comment.contains("gdlint-ignore-next-line") {
-                    // Apply to the next line
-                    let target_line = line_number + 1;
-                    ignore_map.entry(target_line).or_default().extend(rules);
-                } else if comment.contains("gdlint-ignore-line")
-                    || comment.contains("gdlint-ignore")
-                {
-                    // Apply to the current line
-                    ignore_map.entry(line_number).or_default().extend(rules);
+struct Suppression {
+    comment: CommentPosition,
+    /// First and last lines where issues are suppressed, starting at 1.
+    lines: RangeInclusive<usize>,
+    /// Suppressed rules, in the order they appear in the comment. Empty means
+    /// all rules.
+    rules: Vec<String>,
+    /// Rules that suppressed at least one issue.
+    used_rules: HashSet<String>,
+}
+
+/// The suppression comments of a file.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+    /// Every directive comment with the rules it names, to report unknown
+    /// rule names.
+    comments: Vec<(CommentPosition, Vec<String>)>,
+    /// `gdlint-enable` comments that didn't match any `gdlint-disable`.
+    unmatched_enables: Vec<CommentPosition>,
+}
+
+/// Parses the suppression comments of a file from the `comment` nodes of its
+/// syntax tree, so that `#` characters in strings are not mistaken for
+/// comments.
+pub fn parse_ignore_patterns(tree: &Tree, source_code: &str) -> Suppressions {
+    let root = tree.root_node();
+    let lines: Vec<&str> = source_code.lines().collect();
+    let mut comment_nodes = Vec::new();
+    collect_comments(root, &mut comment_nodes);
+
+    let mut result = Suppressions::default();
+    // gdlint-disable comments waiting for a matching gdlint-enable
+    let mut open_disables: Vec<Suppression> = Vec::new();
+
+    for comment_node in comment_nodes {
+        let Ok(text) = comment_node.utf8_text(source_code.as_bytes()) else {
+            continue;
+        };
+        let Some((directive, rules)) = parse_ignore_comment(text) else {
+            continue;
+        };
+        let row = comment_node.start_position().row;
+        let line_number = row + 1;
+        let comment = CommentPosition {
+            line: line_number,
+            column: comment_node.start_position().column + 1,
+            end_column: comment_node.end_position().column + 1,
+        };
+        result.comments.push((comment, rules.clone()));
+
+        let line = lines.get(row).copied().unwrap_or_default();
+        let is_on_own_line = line[..comment_node.start_position().column]
+            .trim()
+            .is_empty();
+        let target_lines = match directive {
+            Directive::Ignore if is_on_own_line => line_number..=line_number,
+            Directive::Ignore => get_commented_statement_lines(&comment_node),
+            Directive::IgnoreNextLine => get_next_statement_lines(root, &lines, row),
+            Directive::IgnoreFile => 1..=lines.len(),
+            Directive::IgnoreFunction => match find_next_statement(root, &lines, row) {
+                Some(node) if FUNCTION_KINDS.contains(&node.kind()) => get_node_lines(&node),
+                // The comment is not above a function, so it can't suppress
+                // anything and gets reported as unused
+                _ => line_number + 1..=line_number,
+            },
+            Directive::Disable => {
+                open_disables.push(Suppression {
+                    comment,
+                    lines: line_number..=lines.len(),
+                    rules,
+                    used_rules: HashSet::new(),
+                });
+                continue;
+            }
+            Directive::Enable => {
+                let closed = close_disables(&mut open_disables, &rules, line_number);
+                if closed.is_empty() {
+                    result.unmatched_enables.push(comment);
                 }
+                result.suppressions.extend(closed);
+                continue;
             }
+        };
+        result.suppressions.push(Suppression {
+            comment,
+            lines: target_lines,
+            rules,
+            used_rules: HashSet::new(),
+        });
+    }
+
+    result.suppressions.extend(open_disables);
+    result
+}
+
+fn collect_comments<'a>(node: Node<'a>, comments: &mut Vec<Node<'a>>) {
+    if node.kind() == "comment" {
+        comments.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_comments(child, comments);
+    }
+}
+
+/// Parses a comment and returns the directive it contains with the rule
+/// names that follow it. Returns None if the comment is not a suppression
+/// comment.
+fn parse_ignore_comment(comment: &str) -> Option<(Directive, Vec<String>)> {
+    for &(pattern, directive) in DIRECTIVES {
+        let Some(start_idx) = comment.find(pattern) else {
+            continue;
+        };
+        let after_pattern = &comment[start_idx + pattern.len()..];
+        // The keyword must end here, so that a typo like `gdlint-ignore-lines`
+        // doesn't become an ignore comment for a rule named `-lines`
+        if after_pattern.starts_with(|c: char| c.is_alphanumeric() || c == '-') {
+            continue;
         }
+
+        // Rule names can be separated by spaces, commas, or both
+        let mut rules: Vec<String> = Vec::new();
+        for rule in after_pattern
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            if !rules.iter().any(|existing| existing == rule) {
+                rules.push(rule.to_string());
+            }
+        }
+        return Some((directive, rules));
     }
 
-    ignore_map
+    None
 }
 
-/// Parse a single ignore comment and extract the rule names
-fn parse_ignore_comment(comment: &str) -> Option<HashSet<String>> {
-    // Look for gdlint-ignore patterns
-    let patterns = [
-        "gdlint-ignore-next-line",
-        "gdlint-ignore-line",
-        "gdlint-ignore",
-    ];
+/// Ends the open `gdlint-disable` ranges that `rules` enable on the line
+/// before `line_number` and returns them. Without rule names, we close all the
+/// ranges. A range that disables several rules stays open for the rules that
+/// are not enabled.
+fn close_disables(
+    open_disables: &mut Vec<Suppression>,
+    rules: &[String],
+    line_number: usize,
+) -> Vec<Suppression> {
+    let mut closed = Vec::new();
+    let end_line = line_number.saturating_sub(1);
+    if rules.is_empty() {
+        for mut suppression in open_disables.drain(..) {
+            suppression.lines = *suppression.lines.start()..=end_line;
+            closed.push(suppression);
+        }
+        return closed;
+    }
 
-    for pattern in &patterns {
-        if let Some(start_idx) = comment.find(pattern) {
-            let after_pattern = &comment[start_idx + pattern.len()..];
+    open_disables.retain_mut(|suppression| {
+        // We can't enable one rule in a range that disables all rules
+        if suppression.rules.is_empty() {
+            return true;
+        }
+        let (enabled, still_disabled): (Vec<String>, Vec<String>) = suppression
+            .rules
+            .iter()
+            .cloned()
+            .partition(|rule| rules.contains(rule));
+        if enabled.is_empty() {
+            return true;
+        }
+        closed.push(Suppression {
+            comment: suppression.comment,
+            lines: *suppression.lines.start()..=end_line,
+            rules: enabled,
+            used_rules: HashSet::new(),
+        });
+        // The range stays open if some of its rules are still disabled
+        suppression.rules = still_disabled;
+        !suppression.rules.is_empty()
+    });
+    closed
+}
 
-            // Look for rule names after the pattern
-            // They can be separated by spaces, commas, or both
-            let rules_part = after_pattern.trim();
+/// Returns the statement that starts on the first line of code after the
+/// comment on row `comment_row`, skipping other comments. Returns None if that
+/// line continues a statement that started before.
+fn find_next_statement<'a>(root: Node<'a>, lines: &[&str], comment_row: usize) -> Option<Node<'a>> {
+    for (row, line) in lines.iter().enumerate().skip(comment_row + 1) {
+        let column = line.len() - line.trim_start().len();
+        if column == line.len() {
+            continue;
+        }
 
-            if rules_part.is_empty() {
-                // No specific rules mentioned, ignore all rules
-                return Some(HashSet::new()); // Empty set means ignore all
+        let start = Point::new(row, column);
+        let end = Point::new(row, column + 1);
+        let mut node = root.descendant_for_point_range(start, end)?;
+        if node.kind() == "comment" {
+            continue;
+        }
+        while let Some(parent) = node.parent() {
+            if BLOCK_KINDS.contains(&parent.kind()) {
+                break;
             }
+            node = parent;
+        }
+        return (node.start_position().row == row).then_some(node);
+    }
+    None
+}
 
-            // Parse comma and/or space separated rule names
-            let rules: HashSet<String> = rules_part
-                .split(|c: char| c == ',' || c.is_whitespace())
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string())
-                .collect();
+/// Returns the lines a `gdlint-ignore-next-line` comment on row `comment_row`
+/// applies to: all the lines of the next statement, or only the header of
+/// statements with a body, like functions.
+fn get_next_statement_lines(
+    root: Node,
+    lines: &[&str],
+    comment_row: usize,
+) -> RangeInclusive<usize> {
+    match find_next_statement(root, lines, comment_row) {
+        Some(node) => get_statement_header_lines(&node),
+        None => comment_row + 2..=comment_row + 2,
+    }
+}
 
-            return Some(rules);
+/// Returns the lines a `gdlint-ignore` comment at the end of a line applies
+/// to: all the lines of the statement the comment ends, which can span
+/// multiple lines.
+fn get_commented_statement_lines(comment_node: &Node) -> RangeInclusive<usize> {
+    let row = comment_node.start_position().row;
+    let statement = match comment_node.parent() {
+        Some(parent) if BLOCK_KINDS.contains(&parent.kind()) => {
+            let mut sibling = comment_node.prev_named_sibling();
+            while let Some(node) = sibling.filter(|node| node.kind() == "comment") {
+                sibling = node.prev_named_sibling();
+            }
+            sibling.filter(|node| node.end_position().row == row)
+        }
+        Some(mut node) => {
+            while let Some(parent) = node
+                .parent()
+                .filter(|parent| !BLOCK_KINDS.contains(&parent.kind()))
+            {
+                node = parent;
+            }
+            Some(node)
         }
+        None => None,
+    };
+
+    // For statements with a body, the comment only applies to the header
+    // if it's on one of its lines
+    match statement.map(|node| get_statement_header_lines(&node)) {
+        Some(lines) if lines.contains(&(row + 1)) => lines,
+        _ => row + 1..=row + 1,
+    }
+}
+
+/// Returns the lines of a statement, starting at 1. For statements with a
+/// body, like functions or loops, returns only the lines before the body.
+fn get_statement_header_lines(node: &Node) -> RangeInclusive<usize> {
+    let header_end = node
+        .child_by_field_name("body")
+        .and_then(|body| body.prev_sibling());
+    match header_end {
+        Some(header_end) => node.start_position().row + 1..=header_end.end_position().row + 1,
+        None => get_node_lines(node),
     }
+}
 
-    None
+/// Returns all the lines of a node, starting at 1.
+fn get_node_lines(node: &Node) -> RangeInclusive<usize> {
+    let start_row = node.start_position().row;
+    let mut end_row = node.end_position().row;
+    // Some nodes include the line break that ends them
+    if node.end_position().column == 0 && end_row > start_row {
+        end_row -= 1;
+    }
+    start_row + 1..=end_row + 1
 }
 
-/// Check if a specific rule should be ignored for a given line
-pub fn should_ignore_rule(
-    ignore_map: &HashMap<usize, HashSet<String>>,
-    line: usize,
-    rule_name: &str,
-) -> bool {
-    if let Some(ignored_rules) = ignore_map.get(&line) {
-        // If the set is empty, it means ignore all rules
-        ignored_rules.is_empty() || ignored_rules.contains(rule_name)
-    } else {
-        false
+/// Returns true if `rule` is a rule of the linter or another name a rule uses
+/// for some of its issues.
+fn is_known_rule(rule: &str) -> bool {
+    ALL_RULES.iter().any(|definition| definition.name == rule)
+        || RULE_ALIASES.iter().any(|&(alias, _)| alias == rule)
+}
+
+impl Suppressions {
+    /// Returns true if a comment suppresses issues of `rule_name` on `line`,
+    /// and marks the comment as used.
+    pub fn is_suppressed(&mut self, line: usize, rule_name: &str) -> bool {
+        let mut is_suppressed = false;
+        for suppression in &mut self.suppressions {
+            let applies_to_rule = suppression.rules.is_empty()
+                || suppression.rules.iter().any(|rule| rule == rule_name);
+            if applies_to_rule && suppression.lines.contains(&line) {
+                suppression.used_rules.insert(rule_name.to_string());
+                is_suppressed = true;
+            }
+        }
+        is_suppressed
     }
+
+    /// Returns issues for the comments that name unknown rules or that didn't
+    /// suppress any issue. `is_rule_enabled` tells whether a rule ran: we
+    /// don't report suppressions of rules that are turned off.
+    pub fn get_unused_issues(&self, is_rule_enabled: impl Fn(&str) -> bool) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        for (comment, rules) in &self.comments {
+            for rule in rules.iter().filter(|rule| !is_known_rule(rule)) {
+                issues.push(create_issue(
+                    comment,
+                    format!("Unknown rule '{}' in suppression comment", rule),
+                ));
+            }
+        }
+
+        for suppression in &self.suppressions {
+            if suppression.rules.is_empty() {
+                if suppression.used_rules.is_empty() {
+                    issues.push(create_issue(
+                        &suppression.comment,
+                        "Suppression comment doesn't suppress any issue".to_string(),
+                    ));
+                }
+                continue;
+            }
+            for rule in &suppression.rules {
+                if is_known_rule(rule)
+                    && is_rule_enabled(rule)
+                    && !suppression.used_rules.contains(rule)
+                {
+                    issues.push(create_issue(
+                        &suppression.comment,
+                        format!("Suppression of rule '{}' doesn't suppress any issue", rule),
+                    ));
+                }
+            }
+        }
+
+        for comment in &self.unmatched_enables {
+            issues.push(create_issue(
+                comment,
+                "'gdlint-enable' comment doesn't match any 'gdlint-disable' comment".to_string(),
+            ));
+        }
+
+        issues
+    }
+}
+
+fn create_issue(comment: &CommentPosition, message: String) -> LintIssue {
+    LintIssue::new(
+        comment.line,
+        comment.column,
+        UNUSED_SUPPRESSION_RULE.to_string(),
+        LintSeverity::Warning,
+        message,
+    )
+    .with_end(comment.line, comment.end_column)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tree_sitter::Parser;
+
+    fn parse(source: &str) -> Suppressions {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        parse_ignore_patterns(&tree, source)
+    }
 
     #[test]
     fn test_parse_ignore_next_line_single_rule() {
         let source = r#"# gdlint-ignore-next-line private-access
 obj._private_method()"#;
 
-        let ignore_map = parse_ignore_patterns(source);
-        assert_eq!(ignore_map.len(), 1);
+        let mut suppressions = parse(source);
+        assert_eq!(suppressions.suppressions.len(), 1);
 
-        let rules = ignore_map.get(&2).unwrap();
-        assert!(rules.contains("private-access"));
-        assert_eq!(rules.len(), 1);
+        assert!(suppressions.is_suppressed(2, "private-access"));
+        assert!(!suppressions.is_suppressed(2, "constant-name"));
+        assert!(!suppressions.is_suppressed(1, "private-access"));
     }
 
     #[test]
@@ -113,25 +467,23 @@ obj._private_method()"#;
         let source = r#"# gdlint-ignore-next-line private-access,constant-name
 obj._private_method()"#;
 
-        let ignore_map = parse_ignore_patterns(source);
-        assert_eq!(ignore_map.len(), 1);
+        let mut suppressions = parse(source);
+        assert_eq!(suppressions.suppressions.len(), 1);
 
-        let rules = ignore_map.get(&2).unwrap();
-        assert!(rules.contains("private-access"));
-        assert!(rules.contains("constant-name"));
-        assert_eq!(rules.len(), 2);
+        assert!(suppressions.is_suppressed(2, "private-access"));
+        assert!(suppressions.is_suppressed(2, "constant-name"));
+        assert_eq!(suppressions.suppressions[0].rules.len(), 2);
     }
 
     #[test]
     fn test_parse_ignore_current_line() {
         let source = r#"obj._private_method() # gdlint-ignore private-access"#;
 
-        let ignore_map = parse_ignore_patterns(source);
-        assert_eq!(ignore_map.len(), 1);
+        let mut suppressions = parse(source);
+        assert_eq!(suppressions.suppressions.len(), 1);
 
-        let rules = ignore_map.get(&1).unwrap();
-        assert!(rules.contains("private-access"));
-        assert_eq!(rules.len(), 1);
+        assert!(suppressions.is_suppressed(1, "private-access"));
+        assert_eq!(suppressions.suppressions[0].rules.len(), 1);
     }
 
     #[test]
@@ -139,48 +491,135 @@ obj._private_method()"#;
         let source = r#"# gdlint-ignore-next-line
 some_problematic_code()"#;
 
-        let ignore_map = parse_ignore_patterns(source);
-        assert_eq!(ignore_map.len(), 1);
+        let mut suppressions = parse(source);
+        assert_eq!(suppressions.suppressions.len(), 1);
 
-        let rules = ignore_map.get(&2).unwrap();
-        assert!(rules.is_empty()); // Empty means ignore all
+        // No rule names means ignore all rules
+        assert!(suppressions.is_suppressed(2, "private-access"));
+        assert!(suppressions.is_suppressed(2, "any-rule"));
+        assert!(!suppressions.is_suppressed(3, "private-access"));
     }
 
     #[test]
-    fn test_should_ignore_rule() {
-        let mut ignore_map = HashMap::new();
-        let mut rules = HashSet::new();
-        rules.insert("private-access".to_string());
-        rules.insert("constant-name".to_string());
-        ignore_map.insert(5, rules);
+    fn test_parse_with_spaces_and_commas() {
+        let source = r#"# gdlint-ignore-next-line private-access , constant-name  ,  other-rule
+some_code_with_issues()"#;
 
-        assert!(should_ignore_rule(&ignore_map, 5, "private-access"));
-        assert!(should_ignore_rule(&ignore_map, 5, "constant-name"));
-        assert!(!should_ignore_rule(&ignore_map, 5, "other-rule"));
-        assert!(!should_ignore_rule(&ignore_map, 6, "private-access"));
+        let suppressions = parse(source);
+        assert_eq!(
+            suppressions.suppressions[0].rules,
+            vec!["private-access", "constant-name", "other-rule"]
+        );
     }
 
     #[test]
-    fn test_should_ignore_all_rules() {
-        let mut ignore_map = HashMap::new();
-        ignore_map.insert(5, HashSet::new()); // Empty set means ignore all
+    fn test_disable_enable() {
+        let source = r#"# gdlint-disable constant-name, max-line-length
+const a = 1
+# gdlint-enable max-line-length
+const b = 2
+# gdlint-enable
+const c = 3
+# gdlint-disable
+const d = 4"#;
+
+        let mut suppressions = parse(source);
+        assert!(suppressions.is_suppressed(2, "max-line-length"));
+        assert!(!suppressions.is_suppressed(4, "max-line-length"));
+        assert!(suppressions.is_suppressed(4, "constant-name"));
+        assert!(!suppressions.is_suppressed(6, "constant-name"));
+        assert!(suppressions.is_suppressed(8, "constant-name"));
+        assert!(suppressions.unmatched_enables.is_empty());
+    }
 
-        assert!(should_ignore_rule(&ignore_map, 5, "private-access"));
-        assert!(should_ignore_rule(&ignore_map, 5, "any-rule"));
-        assert!(!should_ignore_rule(&ignore_map, 6, "private-access"));
+    #[test]
+    fn test_ignore_function_and_file() {
+        let source = r#"# gdlint-ignore-file class-name
+class_name bad_name
+
+# gdlint-ignore-function private-access
+func _ready():
+	obj._private_method()
+
+	obj._private_method()
+func _process(delta):
+	obj._private_method()"#;
+
+        let mut suppressions = parse(source);
+        assert!(suppressions.is_suppressed(2, "class-name"));
+        assert!(suppressions.is_suppressed(10, "class-name"));
+        assert!(suppressions.is_suppressed(6, "private-access"));
+        assert!(suppressions.is_suppressed(8, "private-access"));
+        assert!(!suppressions.is_suppressed(10, "private-access"));
     }
 
     #[test]
-    fn test_parse_with_spaces_and_commas() {
-        let source = r#"# gdlint-ignore-next-line private-access , constant-name  ,  other-rule
-some_code_with_issues()"#;
+    fn test_unused_suppressions() {
+        let source = r#"# gdlint-ignore-next-line private-access, not-a-rule
+obj._private_method()
+# gdlint-ignore-next-line
+pass
+# gdlint-enable class-name"#;
+
+        let mut suppressions = parse(source);
+        assert!(!suppressions.is_suppressed(2, "constant-name"));
+
+        let issues = suppressions.get_unused_issues(|_| true);
+        let messages: Vec<&str> = issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown rule 'not-a-rule' in suppression comment",
+                "Suppression of rule 'private-access' doesn't suppress any issue",
+                "Suppression comment doesn't suppress any issue",
+                "'gdlint-enable' comment doesn't match any 'gdlint-disable' comment",
+            ]
+        );
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.rule == UNUSED_SUPPRESSION_RULE)
+        );
+
+        // Suppressions of rules that don't run are not reported
+        let issues = suppressions.get_unused_issues(|rule| rule != "private-access");
+        assert_eq!(issues.len(), 3);
+    }
 
-        let ignore_map = parse_ignore_patterns(source);
-        let rules = ignore_map.get(&2).unwrap();
+    #[test]
+    fn test_ignore_multiline_statements() {
+        let source = r#"func _ready():
+	# gdlint-ignore-next-line private-access
+	var value = compute(
+		obj._private_method()
+	)
+	var other_value = compute(
+		obj._private_method()
+	) # gdlint-ignore private-access
+	obj._private_method()
+"#;
+
+        let mut suppressions = parse(source);
+        assert!(suppressions.is_suppressed(4, "private-access"));
+        assert!(suppressions.is_suppressed(7, "private-access"));
+        assert!(!suppressions.is_suppressed(9, "private-access"));
+        // The comment above a function only applies to its definition
+        let source = "# gdlint-ignore-next-line\nfunc _ready():\n\tobj._private_method()\n";
+        let mut suppressions = parse(source);
+        assert!(suppressions.is_suppressed(2, "function-name"));
+        assert!(!suppressions.is_suppressed(3, "private-access"));
+    }
 
-        assert!(rules.contains("private-access"));
-        assert!(rules.contains("constant-name"));
-        assert!(rules.contains("other-rule"));
-        assert_eq!(rules.len(), 3);
+    #[test]
+    fn test_ignore_comment_in_string() {
+        let source = r##"var color = "#ff0000" # gdlint-ignore constant-name
+var text = "# gdlint-ignore-next-line"
+var bad_name = 1
+"##;
+
+        let mut suppressions = parse(source);
+        assert_eq!(suppressions.suppressions.len(), 1);
+        assert_eq!(suppressions.suppressions[0].rules, vec!["constant-name"]);
+        assert!(!suppressions.is_suppressed(3, "variable-name"));
     }
 }
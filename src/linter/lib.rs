@@ -8,3 +8,162 @@ pub fn get_line_column(node: &Node) -> (usize, usize) {
     let start_position = node.start_position();
     (start_position.row + 1, start_position.column + 1)
 }
+
+/// Returns the line and column right after the end of the node, starting at 1.
+pub fn get_end_line_column(node: &Node) -> (usize, usize) {
+    let end_position = node.end_position();
+    (end_position.row + 1, end_position.column + 1)
+}
+
+/// Virtual methods that Godot calls on scripts. They start with an underscore
+/// but are called by the engine rather than by the script.
+pub const VIRTUAL_METHODS: &[&str] = &[
+    "_init",
+    "_static_init",
+    "_ready",
+    "_enter_tree",
+    "_exit_tree",
+    "_process",
+    "_physics_process",
+    "_input",
+    "_unhandled_input",
+    "_unhandled_key_input",
+    "_shortcut_input",
+    "_gui_input",
+    "_notification",
+    "_draw",
+    "_get",
+    "_set",
+    "_get_property_list",
+    "_property_can_revert",
+    "_property_get_revert",
+    "_validate_property",
+    "_to_string",
+    "_get_configuration_warning",
+    "_get_configuration_warnings",
+    "_integrate_forces",
+    "_can_drop_data",
+    "_drop_data",
+    "_get_drag_data",
+    "_has_point",
+    "_make_custom_tooltip",
+    "_iter_init",
+    "_iter_next",
+    "_iter_get",
+    "_run",
+    "_handles",
+    "_edit",
+    "_make_visible",
+    "_has_main_screen",
+    "_get_plugin_name",
+    "_get_plugin_icon",
+    "_enable_plugin",
+    "_disable_plugin",
+    "_forward_canvas_gui_input",
+    "_forward_3d_gui_input",
+    "_apply_changes",
+    "_save_external_data",
+    "_clear",
+    "_build",
+    "_get_state",
+    "_set_state",
+];
+
+/// Statements that make the code after them in a block unreachable.
+pub const JUMP_STATEMENTS: &[&str] = &["return_statement", "break_statement", "continue_statement"];
+
+/// Returns the statements of a body, skipping comments and region markers.
+pub fn get_statements<'tree>(body_node: &Node<'tree>) -> Vec<Node<'tree>> {
+    let mut cursor = body_node.walk();
+    body_node
+        .named_children(&mut cursor)
+        .filter(|child| !matches!(child.kind(), "comment" | "region_start" | "region_end"))
+        .collect()
+}
+
+/// Returns true if the code after the body never runs, because every path
+/// through the body ends with one of the `exit_kinds` statements, like
+/// `return_statement`, or with an infinite loop.
+pub fn body_always_exits(body_node: &Node, exit_kinds: &[&str], source_code: &str) -> bool {
+    get_statements(body_node)
+        .iter()
+        .any(|statement| statement_always_exits(statement, exit_kinds, source_code))
+}
+
+/// Returns true if the code after the statement never runs. This is the case
+/// for the `exit_kinds` statements, if and match statements where every
+/// branch exits, and `while true` loops without a `break`.
+pub fn statement_always_exits(statement: &Node, exit_kinds: &[&str], source_code: &str) -> bool {
+    if exit_kinds.contains(&statement.kind()) {
+        return true;
+    }
+
+    let body_exits = |node: &Node| {
+        node.child_by_field_name("body")
+            .is_some_and(|body| body_always_exits(&body, exit_kinds, source_code))
+    };
+    let mut cursor = statement.walk();
+    match statement.kind() {
+        "if_statement" => {
+            let mut has_else = false;
+            let branches_exit = statement
+                .children(&mut cursor)
+                .all(|child| match child.kind() {
+                    "elif_clause" => body_exits(&child),
+                    "else_clause" => {
+                        has_else = true;
+                        body_exits(&child)
+                    }
+                    _ => true,
+                });
+            has_else && body_exits(statement) && branches_exit
+        }
+        // Without a pattern that matches any value, like `_` or `var x`,
+        // values that match no pattern skip the whole statement. A pattern
+        // with a `when` guard doesn't match every value, even `_`
+        "match_statement" => {
+            let Some(match_body) = statement.child_by_field_name("body") else {
+                return false;
+            };
+            let sections: Vec<Node> = match_body
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == "pattern_section")
+                .collect();
+            let has_wildcard = sections.iter().any(|section| {
+                let mut section_cursor = section.walk();
+                let patterns: Vec<Node> = section.named_children(&mut section_cursor).collect();
+                let has_guard = patterns
+                    .iter()
+                    .any(|pattern| pattern.kind() == "pattern_guard");
+                !has_guard
+                    && patterns.iter().any(|pattern| {
+                        pattern.kind() == "pattern_binding"
+                            || get_node_text(pattern, source_code) == "_"
+                    })
+            });
+            has_wildcard && sections.iter().all(body_exits)
+        }
+        "while_statement" => {
+            let is_infinite = statement
+                .child_by_field_name("condition")
+                .is_some_and(|condition| condition.kind() == "true");
+            is_infinite
+                && statement
+                    .child_by_field_name("body")
+                    .is_some_and(|body| !contains_break(&body))
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the node contains a `break` statement for the loop that
+/// contains the node. We skip nested loops and lambdas.
+fn contains_break(node: &Node) -> bool {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| match child.kind() {
+            "break_statement" => true,
+            "for_statement" | "while_statement" | "lambda" => false,
+            _ => contains_break(&child),
+        })
+}
@@ -0,0 +1,331 @@
+//! Code metrics computed for each function of a file: its length, number of
+//! parameters, nesting depth, number of return statements, and cyclomatic
+//! complexity. The max-* and cyclomatic-complexity rules compare them to the
+//! thresholds of the linter configuration, and the `metrics` command prints
+//! them as a table.
+//!
+//! We compute metrics for functions, constructors, and lambdas. The code of a
+//! lambda only counts towards the metrics of the lambda, not the ones of the
+//! function that contains it.
+//!
+//! The cyclomatic complexity starts at 1 and increases by 1 for each branch
+//! in the code: if and elif clauses, match pattern sections, while and for
+//! loops, and and or operators, and ternary expressions.
+use std::fs;
+use std::path::PathBuf;
+
+use tree_sitter::{Node, Parser};
+
+use super::lib::{get_line_column, get_node_text};
+use crate::files::find_gdscript_files;
+
+/// Node kinds that have their own metrics.
+pub const FUNCTION_KINDS: &[&str] = &["function_definition", "constructor_definition", "lambda"];
+
+/// Node kinds that add a branch to the code, not counting boolean operators.
+const BRANCH_KINDS: &[&str] = &[
+    "if_statement",
+    "elif_clause",
+    "pattern_section",
+    "while_statement",
+    "for_statement",
+    "conditional_expression",
+];
+
+/// Metrics of a function, constructor, or lambda.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub line: usize,
+    /// Number of lines from the function's signature to the end of its body.
+    pub length: usize,
+    pub parameters: usize,
+    /// Deepest level of nested blocks in the body. Statements directly in the
+    /// body of the function have a depth of 0.
+    pub nesting_depth: usize,
+    pub returns: usize,
+    pub cyclomatic_complexity: usize,
+}
+
+impl FunctionMetrics {
+    pub fn new(function_node: &Node, source_code: &str) -> Self {
+        let (line, _) = get_line_column(function_node);
+        Self {
+            name: get_function_name(function_node, source_code),
+            line,
+            length: count_lines(function_node),
+            parameters: count_parameters(function_node),
+            nesting_depth: get_nesting_depth(function_node),
+            returns: count_returns(function_node),
+            cyclomatic_complexity: get_cyclomatic_complexity(function_node, source_code),
+        }
+    }
+}
+
+/// Returns the name of a function, `_init` for constructors, and `<lambda>`
+/// for lambdas without a name.
+pub fn get_function_name(function_node: &Node, source_code: &str) -> String {
+    if function_node.kind() == "constructor_definition" {
+        return "_init".to_string();
+    }
+    function_node
+        .child_by_field_name("name")
+        .map(|name_node| get_node_text(&name_node, source_code).to_string())
+        .unwrap_or_else(|| "<lambda>".to_string())
+}
+
+/// Returns the node that issues about a function's metrics point to: the
+/// name of the function, or its first token if it has no name.
+pub fn get_report_node<'tree>(function_node: &Node<'tree>) -> Node<'tree> {
+    function_node
+        .child_by_field_name("name")
+        .or_else(|| function_node.child(0))
+        .unwrap_or(*function_node)
+}
+
+/// Returns the number of lines the node spans, including blank lines and
+/// comments inside of it.
+pub fn count_lines(node: &Node) -> usize {
+    let start_row = node.start_position().row;
+    let end_position = node.end_position();
+    // The node can end at the start of the line after its last statement
+    let end_row = if end_position.column == 0 && end_position.row > start_row {
+        end_position.row - 1
+    } else {
+        end_position.row
+    };
+    end_row - start_row + 1
+}
+
+pub fn count_parameters(function_node: &Node) -> usize {
+    let Some(parameters_node) = function_node.child_by_field_name("parameters") else {
+        return 0;
+    };
+    let mut cursor = parameters_node.walk();
+    parameters_node
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() != "comment")
+        .count()
+}
+
+pub fn get_nesting_depth(function_node: &Node) -> usize {
+    let mut max_depth = 0;
+    visit_function_body(function_node, &mut |_node, depth| {
+        max_depth = max_depth.max(depth);
+    });
+    max_depth
+}
+
+pub fn count_returns(function_node: &Node) -> usize {
+    let mut returns = 0;
+    visit_function_body(function_node, &mut |node, _depth| {
+        if node.kind() == "return_statement" {
+            returns += 1;
+        }
+    });
+    returns
+}
+
+pub fn get_cyclomatic_complexity(function_node: &Node, source_code: &str) -> usize {
+    let mut complexity = 1;
+    visit_function_body(function_node, &mut |node, _depth| {
+        if BRANCH_KINDS.contains(&node.kind()) || is_boolean_operator(node, source_code) {
+            complexity += 1;
+        }
+    });
+    complexity
+}
+
+fn is_boolean_operator(node: &Node, source_code: &str) -> bool {
+    node.kind() == "binary_operator"
+        && node.child_by_field_name("op").is_some_and(|op_node| {
+            matches!(
+                get_node_text(&op_node, source_code),
+                "and" | "&&" | "or" | "||"
+            )
+        })
+}
+
+/// Calls `visit` with each node in the body of the function and the number of
+/// blocks the node is nested in, skipping the code of nested lambdas.
+fn visit_function_body(function_node: &Node, visit: &mut impl FnMut(&Node, usize)) {
+    if let Some(body_node) = function_node.child_by_field_name("body") {
+        visit_children(&body_node, 0, visit);
+    }
+}
+
+fn visit_children(node: &Node, depth: usize, visit: &mut impl FnMut(&Node, usize)) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "lambda" => {}
+            "body" => visit_children(&child, depth + 1, visit),
+            _ => {
+                visit(&child, depth);
+                visit_children(&child, depth, visit);
+            }
+        }
+    }
+}
+
+/// Returns the metrics of every function in the file, in the order they
+/// appear in the code.
+pub fn compute_metrics(source_code: &str) -> Result<Vec<FunctionMetrics>, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+        .map_err(|e| format!("Failed to set language: {}", e))?;
+    let tree = parser
+        .parse(source_code, None)
+        .ok_or("Failed to parse GDScript code")?;
+
+    let mut metrics = Vec::new();
+    collect_metrics(&tree.root_node(), source_code, &mut metrics);
+    Ok(metrics)
+}
+
+fn collect_metrics(node: &Node, source_code: &str, metrics: &mut Vec<FunctionMetrics>) {
+    if FUNCTION_KINDS.contains(&node.kind()) {
+        metrics.push(FunctionMetrics::new(node, source_code));
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_metrics(&child, source_code, metrics);
+    }
+}
+
+/// Prints a table with the metrics of each function in the GDScript files
+/// found in `input_files`.
+pub fn print_metrics(input_files: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let gdscript_files = find_gdscript_files(input_files)?;
+    if gdscript_files.is_empty() {
+        return Err("No GDScript files found in the arguments provided".into());
+    }
+
+    for (index, file_path) in gdscript_files.iter().enumerate() {
+        let source_code = fs::read_to_string(file_path)
+            .map_err(|error| format!("Failed to read file {}: {}", file_path.display(), error))?;
+        let metrics = compute_metrics(&source_code)?;
+
+        if index > 0 {
+            println!();
+        }
+        println!(
+            "{} ({} lines)",
+            file_path.display(),
+            source_code.lines().count()
+        );
+        print!("{}", format_metrics_table(&metrics));
+    }
+    Ok(())
+}
+
+/// Formats the metrics as a table with one row per function, aligning the
+/// columns to the longest function name.
+pub fn format_metrics_table(metrics: &[FunctionMetrics]) -> String {
+    let headers = [
+        "Function",
+        "Line",
+        "Length",
+        "Params",
+        "Depth",
+        "Returns",
+        "Complexity",
+    ];
+    let name_width = metrics
+        .iter()
+        .map(|function| function.name.chars().count())
+        .chain(std::iter::once(headers[0].len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut output = format!("  {:<name_width$}", headers[0]);
+    for header in &headers[1..] {
+        output.push_str(&format!("  {}", header));
+    }
+    output.push('\n');
+
+    for function in metrics {
+        let values = [
+            function.line,
+            function.length,
+            function.parameters,
+            function.nesting_depth,
+            function.returns,
+            function.cyclomatic_complexity,
+        ];
+        output.push_str(&format!("  {:<name_width$}", function.name));
+        for (header, value) in headers[1..].iter().zip(values) {
+            output.push_str(&format!("  {:>width$}", value, width = header.len()));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_metrics() {
+        let source_code = r#"func process(items, limit = 10):
+	for item in items:
+		if item > limit and limit > 0:
+			return item
+		elif item < 0:
+			match item:
+				-1:
+					return 0
+				_:
+					pass
+	var callback = func(value):
+		if value:
+			return value
+		return null
+	return limit if callback.call(items) else -1
+"#;
+        let metrics = compute_metrics(source_code).unwrap();
+        assert_eq!(
+            metrics,
+            vec![
+                FunctionMetrics {
+                    name: "process".to_string(),
+                    line: 1,
+                    length: 15,
+                    parameters: 2,
+                    nesting_depth: 3,
+                    returns: 3,
+                    cyclomatic_complexity: 8,
+                },
+                FunctionMetrics {
+                    name: "<lambda>".to_string(),
+                    line: 11,
+                    length: 4,
+                    parameters: 1,
+                    nesting_depth: 1,
+                    returns: 2,
+                    cyclomatic_complexity: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_metrics_table() {
+        let metrics = vec![FunctionMetrics {
+            name: "_ready".to_string(),
+            line: 3,
+            length: 12,
+            parameters: 0,
+            nesting_depth: 2,
+            returns: 1,
+            cyclomatic_complexity: 4,
+        }];
+        assert_eq!(
+            format_metrics_table(&metrics),
+            "  Function  Line  Length  Params  Depth  Returns  Complexity\n  \
+             _ready       3      12       0      2        1           4\n"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! This module turns lint issues into the output formats the linter supports
+//! on top of the default one-line-per-issue text format:
+//!
+//! - JSON, a flat list of issues for scripts and other tools
+//! - SARIF, the format GitHub code scanning and many CI services read
+//! - GitHub Actions workflow commands, which show issues as annotations on
+//!   pull requests
+use serde_json::{Value, json};
+
+use super::rules::ALL_RULES;
+use super::{LintIssue, LintSeverity};
+
+/// The issues found in one file, along with the path of the file as it should
+/// appear in the output.
+pub type FileIssues = (String, Vec<LintIssue>);
+
+/// Format used to print lint issues.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// One line per issue: `path:line:rule:severity: message`
+    #[default]
+    Text,
+    /// Issues grouped by file and line, for people reading in a terminal
+    Pretty,
+    Json,
+    Sarif,
+    Github,
+}
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const TOOL_NAME: &str = "gdscript-formatter";
+const TOOL_URL: &str = "https://github.com/gdquest/gdscript-formatter";
+
+/// Returns the SARIF level matching a severity.
+fn get_sarif_level(severity: LintSeverity) -> &'static str {
+    match severity {
+        LintSeverity::Error => "error",
+        LintSeverity::Warning => "warning",
+        LintSeverity::Info => "note",
+    }
+}
+
+/// Returns the GitHub workflow command matching a severity.
+fn get_github_command(severity: LintSeverity) -> &'static str {
+    match severity {
+        LintSeverity::Error => "error",
+        LintSeverity::Warning => "warning",
+        LintSeverity::Info => "notice",
+    }
+}
+
+/// Returns all the issues as a JSON array of objects.
+pub fn format_json(file_issues: &[FileIssues]) -> String {
+    let issues: Vec<Value> = file_issues
+        .iter()
+        .flat_map(|(file_path, issues)| {
+            issues.iter().map(move |issue| {
+                json!({
+                    "file": file_path,
+                    "line": issue.line,
+                    "column": issue.column,
+                    "end_line": issue.end_line,
+                    "end_column": issue.end_column,
+                    "rule": issue.rule,
+                    "severity": issue.severity.as_str(),
+                    "message": issue.message,
+                })
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&issues).unwrap_or_default()
+}
+
+/// Returns all the issues as a SARIF 2.1.0 log with a single run.
+pub fn format_sarif(file_issues: &[FileIssues]) -> String {
+    let rules: Vec<Value> = ALL_RULES
+        .iter()
+        .map(|rule| json!({ "id": rule.name }))
+        .collect();
+
+    let results: Vec<Value> = file_issues
+        .iter()
+        .flat_map(|(file_path, issues)| {
+            let uri = file_path.replace('\\', "/");
+            issues.iter().map(move |issue| {
+                json!({
+                    "ruleId": issue.rule,
+                    "level": get_sarif_level(issue.severity),
+                    "message": { "text": issue.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": uri },
+                            "region": {
+                                "startLine": issue.line,
+                                "startColumn": issue.column,
+                                "endLine": issue.end_line,
+                                "endColumn": issue.end_column,
+                            },
+                        },
+                    }],
+                })
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "informationUri": TOOL_URL,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+/// Returns one GitHub Actions workflow command per issue, like
+/// `::warning file=player.gd,line=3,col=5,endLine=3,endColumn=9,title=variable-name::message`.
+pub fn format_github(file_issues: &[FileIssues]) -> String {
+    let mut output = String::new();
+    for (file_path, issues) in file_issues {
+        for issue in issues {
+            output.push_str(&format!(
+                "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+                get_github_command(issue.severity),
+                escape_github_property(file_path),
+                issue.line,
+                issue.column,
+                issue.end_line,
+                issue.end_column,
+                escape_github_property(&issue.rule),
+                escape_github_data(&issue.message),
+            ));
+        }
+    }
+    output
+}
+
+/// Escapes the message of a workflow command.
+fn escape_github_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command, like the file path.
+fn escape_github_property(text: &str) -> String {
+    escape_github_data(text)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_issues() -> Vec<FileIssues> {
+        vec![(
+            "scripts/player.gd".to_string(),
+            vec![
+                LintIssue::new(
+                    3,
+                    5,
+                    "variable-name".to_string(),
+                    LintSeverity::Error,
+                    "Variable name 'Speed' should be in snake_case".to_string(),
+                )
+                .with_end(3, 10),
+            ],
+        )]
+    }
+
+    #[test]
+    fn test_format_json() {
+        let output: Value = serde_json::from_str(&format_json(&create_test_issues())).unwrap();
+        assert_eq!(
+            output,
+            json!([{
+                "file": "scripts/player.gd",
+                "line": 3,
+                "column": 5,
+                "end_line": 3,
+                "end_column": 10,
+                "rule": "variable-name",
+                "severity": "error",
+                "message": "Variable name 'Speed' should be in snake_case",
+            }])
+        );
+    }
+
+    #[test]
+    fn test_format_sarif() {
+        let output: Value = serde_json::from_str(&format_sarif(&create_test_issues())).unwrap();
+        let result = &output["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "variable-name");
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "scripts/player.gd");
+        assert_eq!(location["region"]["endColumn"], 10);
+    }
+
+    #[test]
+    fn test_format_github() {
+        assert_eq!(
+            format_github(&create_test_issues()),
+            "::error file=scripts/player.gd,line=3,col=5,endLine=3,endColumn=10,title=variable-name::Variable name 'Speed' should be in snake_case\n"
+        );
+        assert_eq!(escape_github_property("a,b:c"), "a%2Cb%3Ac");
+        assert_eq!(escape_github_data("50%\nof"), "50%25%0Aof");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::linter::rule_config::RuleSettings;
 use regex::Regex;
 use std::sync::LazyLock;
 
@@ -20,3 +21,52 @@ pub static CONSTANT_CASE: LazyLock<Regex> =
 /// _PRIVATE_CONSTANT_CASE
 pub static PRIVATE_CONSTANT_CASE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^_[A-Z][A-Z0-9_]*$").unwrap());
+
+/// Name of the option naming rules use to replace their default naming
+/// convention with a custom regular expression.
+pub const PATTERN_OPTION: &str = "pattern";
+
+/// The naming convention a naming rule checks: either the rule's default
+/// convention or the regular expression set with the `pattern` option.
+pub struct NamingPattern {
+    custom: Option<Regex>,
+}
+
+impl NamingPattern {
+    pub fn from_settings(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            custom: settings.get_regex(PATTERN_OPTION)?,
+        })
+    }
+
+    /// Returns true if `name` follows the naming convention. Without a custom
+    /// pattern, `is_default_match` checks the rule's default convention.
+    pub fn is_match(&self, name: &str, is_default_match: impl FnOnce(&str) -> bool) -> bool {
+        match &self.custom {
+            Some(pattern) => pattern.is_match(name),
+            None => is_default_match(name),
+        }
+    }
+
+    /// Returns the end of issue messages describing the convention, like
+    /// "be in snake_case format", where `default` describes the rule's
+    /// default convention.
+    pub fn describe(&self, default: &str) -> String {
+        match &self.custom {
+            Some(pattern) => format!("match the pattern '{}'", pattern.as_str()),
+            None => default.to_string(),
+        }
+    }
+
+    /// Like `describe()`, for names of loaded resources, which can also use
+    /// PascalCase.
+    pub fn describe_with_pascal_case(&self, default: &str) -> String {
+        match &self.custom {
+            Some(pattern) => format!(
+                "be in PascalCase format or match the pattern '{}'",
+                pattern.as_str()
+            ),
+            None => default.to_string(),
+        }
+    }
+}
@@ -1,5 +1,91 @@
 use crate::linter::rules::ALL_RULES;
-use std::collections::HashSet;
+use crate::linter::{LintSeverity, LinterConfig};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// Severity of a rule set in the configuration. `Off` disables the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
+impl RuleSeverity {
+    /// Returns the severity of the issues reported by the rule, or None if
+    /// the rule is off.
+    pub fn to_lint_severity(self) -> Option<LintSeverity> {
+        match self {
+            RuleSeverity::Error => Some(LintSeverity::Error),
+            RuleSeverity::Warning => Some(LintSeverity::Warning),
+            RuleSeverity::Info => Some(LintSeverity::Info),
+            RuleSeverity::Off => None,
+        }
+    }
+}
+
+/// Settings of one rule, from a `[lint.rules.<rule-name>]` table of the
+/// configuration file. Every key other than `severity` is an option of the
+/// rule, like `pattern` for naming rules.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RuleSettings {
+    /// Overrides the severity of the issues the rule reports.
+    pub severity: Option<RuleSeverity>,
+    #[serde(flatten)]
+    pub options: HashMap<String, toml::Value>,
+}
+
+impl RuleSettings {
+    /// Overrides these settings with the ones set in `other`, option by
+    /// option.
+    pub fn merge(&mut self, other: &RuleSettings) {
+        if other.severity.is_some() {
+            self.severity = other.severity;
+        }
+        for (key, value) in &other.options {
+            self.options.insert(key.clone(), value.clone());
+        }
+    }
+
+    /// Returns the regular expression set for the option `key`, if any.
+    pub fn get_regex(&self, key: &str) -> Result<Option<Regex>, String> {
+        let Some(value) = self.options.get(key) else {
+            return Ok(None);
+        };
+        let pattern = value
+            .as_str()
+            .ok_or_else(|| format!("Option '{}' must be a string", key))?;
+        Regex::new(pattern)
+            .map(Some)
+            .map_err(|error| format!("Invalid regular expression for option '{}': {}", key, error))
+    }
+
+    /// Returns the boolean set for the option `key`, if any.
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, String> {
+        let Some(value) = self.options.get(key) else {
+            return Ok(None);
+        };
+        value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| format!("Option '{}' must be true or false", key))
+    }
+
+    /// Returns the number set for the option `key`, if any.
+    pub fn get_usize(&self, key: &str) -> Result<Option<usize>, String> {
+        let Some(value) = self.options.get(key) else {
+            return Ok(None);
+        };
+        value
+            .as_integer()
+            .and_then(|number| usize::try_from(number).ok())
+            .map(Some)
+            .ok_or_else(|| format!("Option '{}' must be a positive integer", key))
+    }
+}
 
 /// Parse disabled rules from command line arguments or configuration
 pub fn parse_disabled_rules(rules_string: &str) -> HashSet<String> {
@@ -30,3 +116,29 @@ pub fn validate_rule_names(rules: &HashSet<String>) -> Result<(), Vec<String>> {
         Err(invalid_rules)
     }
 }
+
+/// Checks that the settings only configure existing rules with the options
+/// they support, and that the rules accept the option values.
+pub fn validate_rule_settings(
+    rules: &HashMap<String, RuleSettings>,
+    config: &LinterConfig,
+) -> Result<(), String> {
+    for (rule_name, settings) in rules {
+        let Some(definition) = ALL_RULES.iter().find(|rule| rule.name == rule_name) else {
+            return Err(format!("Unknown rule '{}'", rule_name));
+        };
+        if let Some(option) = settings
+            .options
+            .keys()
+            .find(|option| !definition.options.contains(&option.as_str()))
+        {
+            return Err(format!(
+                "Unknown option '{}' for rule '{}'",
+                option, rule_name
+            ));
+        }
+        (definition.create)(config, settings)
+            .map_err(|error| format!("Invalid settings for rule '{}': {}", rule_name, error))?;
+    }
+    Ok(())
+}
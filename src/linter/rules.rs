@@ -1,21 +1,42 @@
 pub mod class_name;
 pub mod comparison_with_itself;
 pub mod constant_name;
+pub mod cyclomatic_complexity;
 pub mod duplicated_load;
 pub mod enum_member_name;
 pub mod enum_name;
 pub mod function_argument_name;
 pub mod function_name;
+pub mod godot3_syntax;
 pub mod loop_variable_name;
+pub mod max_file_length;
+pub mod max_function_length;
 pub mod max_line_length;
+pub mod max_nesting_depth;
+pub mod max_parameters;
+pub mod max_returns;
+pub mod missing_return_type;
 pub mod no_else_return;
 pub mod private_access;
+pub mod shadowed_variable;
 pub mod signal_name;
 pub mod standalone_expression;
 pub mod unnecessary_pass;
+pub mod unreachable_code;
+pub mod untyped_declaration;
+pub mod untyped_loop_variable;
+pub mod untyped_parameter;
 pub mod unused_argument;
+pub mod unused_private_member;
+pub mod unused_signal;
+pub mod unused_suppression;
+pub mod unused_variable;
 pub mod variable_name;
 
+use crate::linter::regex_patterns::PATTERN_OPTION;
+use crate::linter::rule_config::RuleSettings;
+use crate::linter::scope::ScopeTree;
+use crate::linter::static_typing::STATIC_TYPING_OPTIONS;
 use crate::linter::{LintIssue, LinterConfig};
 use tree_sitter::Node;
 
@@ -40,6 +61,13 @@ pub trait Rule {
         Vec::new()
     }
 
+    /// This is called once after traversing the AST with the scopes and
+    /// symbols of the file, for rules that need to know which declaration
+    /// each name refers to, like detecting unused variables.
+    fn check_scopes(&mut self, _scopes: &ScopeTree, _source_code: &str) -> Vec<LintIssue> {
+        Vec::new()
+    }
+
     /// This is called after traversing the AST for rules that collect data
     /// during traversal and need to process it afterwards. For example,
     /// detecting duplicated loads by collecting all load paths first, then
@@ -52,99 +80,247 @@ pub trait Rule {
 use class_name::ClassNameRule;
 use comparison_with_itself::ComparisonWithItselfRule;
 use constant_name::ConstantNameRule;
+use cyclomatic_complexity::CyclomaticComplexityRule;
 use duplicated_load::DuplicatedLoadRule;
 use enum_member_name::EnumMemberNameRule;
 use enum_name::EnumNameRule;
 use function_argument_name::FunctionArgumentNameRule;
 use function_name::FunctionNameRule;
+use godot3_syntax::Godot3SyntaxRule;
 use loop_variable_name::LoopVariableNameRule;
+use max_file_length::MaxFileLengthRule;
+use max_function_length::MaxFunctionLengthRule;
 use max_line_length::MaxLineLengthRule;
+use max_nesting_depth::MaxNestingDepthRule;
+use max_parameters::MaxParametersRule;
+use max_returns::MaxReturnsRule;
+use missing_return_type::MissingReturnTypeRule;
 use no_else_return::NoElseReturnRule;
 use private_access::PrivateAccessRule;
+use shadowed_variable::ShadowedVariableRule;
 use signal_name::SignalNameRule;
 use standalone_expression::StandaloneExpressionRule;
 use unnecessary_pass::UnnecessaryPassRule;
+use unreachable_code::UnreachableCodeRule;
+use untyped_declaration::UntypedDeclarationRule;
+use untyped_loop_variable::UntypedLoopVariableRule;
+use untyped_parameter::UntypedParameterRule;
 use unused_argument::UnusedArgumentRule;
+use unused_private_member::UnusedPrivateMemberRule;
+use unused_signal::UnusedSignalRule;
+use unused_suppression::UnusedSuppressionRule;
+use unused_variable::UnusedVariableRule;
 use variable_name::VariableNameRule;
 
+/// Creates a rule from the linter configuration and the rule's settings.
+/// Returns an error if an option has an invalid value.
+pub type CreateRule = fn(&LinterConfig, &RuleSettings) -> Result<Box<dyn Rule>, String>;
+
 pub struct RuleDefinition {
     pub name: &'static str,
-    pub create: fn(&LinterConfig) -> Box<dyn Rule>,
+    /// Names of the options users can set for this rule in the configuration.
+    pub options: &'static [&'static str],
+    pub create: CreateRule,
 }
 
+/// Names that rules use for some of their issues instead of their own name,
+/// with the name of the rule reporting them.
+pub const RULE_ALIASES: &[(&str, &str)] = &[("load-variable-name", "variable-name")];
+
+/// Returns the name of the rule that reports issues under `rule`, which is
+/// `rule` itself unless it's an alias.
+pub fn resolve_rule_alias(rule: &str) -> &str {
+    RULE_ALIASES
+        .iter()
+        .find(|&&(alias, _)| alias == rule)
+        .map_or(rule, |&(_, rule_name)| rule_name)
+}
+
+/// Rules that only run when the configuration has a settings table for them,
+/// as they enforce conventions that many projects don't follow, like static
+/// typing. Setting their severity to "off" still turns them off.
+pub const OPT_IN_RULES: &[&str] = &[
+    "untyped-declaration",
+    "missing-return-type",
+    "untyped-parameter",
+    "untyped-loop-variable",
+];
+
 /// List of all the rules available in the linter. The linter will only run
 /// these plus the ones that have not been disabled in the config.
 pub const ALL_RULES: &[RuleDefinition] = &[
     RuleDefinition {
         name: "duplicated-load",
-        create: |_config| {
-            Box::new(DuplicatedLoadRule {
+        options: &[],
+        create: |_config, _settings| {
+            Ok(Box::new(DuplicatedLoadRule {
                 load_paths: std::collections::HashMap::new(),
-            })
+            }))
         },
     },
     RuleDefinition {
         name: "standalone-expression",
-        create: |_config| Box::new(StandaloneExpressionRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(StandaloneExpressionRule)),
     },
     RuleDefinition {
         name: "unnecessary-pass",
-        create: |_config| Box::new(UnnecessaryPassRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnnecessaryPassRule)),
     },
     RuleDefinition {
         name: "unused-argument",
-        create: |_config| Box::new(UnusedArgumentRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnusedArgumentRule)),
+    },
+    RuleDefinition {
+        name: "unused-variable",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnusedVariableRule)),
+    },
+    RuleDefinition {
+        name: "unused-private-member",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnusedPrivateMemberRule)),
+    },
+    RuleDefinition {
+        name: "unused-signal",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnusedSignalRule)),
+    },
+    RuleDefinition {
+        name: "shadowed-variable",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(ShadowedVariableRule)),
     },
     RuleDefinition {
         name: "comparison-with-itself",
-        create: |_config| Box::new(ComparisonWithItselfRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(ComparisonWithItselfRule)),
     },
     RuleDefinition {
         name: "private-access",
-        create: |_config| Box::new(PrivateAccessRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(PrivateAccessRule)),
     },
     RuleDefinition {
         name: "max-line-length",
-        create: |config| Box::new(MaxLineLengthRule::new(config)),
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxLineLengthRule::new(config))),
+    },
+    RuleDefinition {
+        name: "max-file-length",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxFileLengthRule::new(config))),
+    },
+    RuleDefinition {
+        name: "max-function-length",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxFunctionLengthRule::new(config))),
+    },
+    RuleDefinition {
+        name: "max-parameters",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxParametersRule::new(config))),
+    },
+    RuleDefinition {
+        name: "max-nesting-depth",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxNestingDepthRule::new(config))),
+    },
+    RuleDefinition {
+        name: "max-returns",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(MaxReturnsRule::new(config))),
+    },
+    RuleDefinition {
+        name: "cyclomatic-complexity",
+        options: &[],
+        create: |config, _settings| Ok(Box::new(CyclomaticComplexityRule::new(config))),
     },
     RuleDefinition {
         name: "no-else-return",
-        create: |_config| Box::new(NoElseReturnRule),
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(NoElseReturnRule)),
+    },
+    RuleDefinition {
+        name: "unreachable-code",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnreachableCodeRule)),
+    },
+    RuleDefinition {
+        name: "godot3-syntax",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(Godot3SyntaxRule)),
     },
     RuleDefinition {
         name: "function-name",
-        create: |_config| Box::new(FunctionNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(FunctionNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "class-name",
-        create: |_config| Box::new(ClassNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(ClassNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "signal-name",
-        create: |_config| Box::new(SignalNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(SignalNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "variable-name",
-        create: |_config| Box::new(VariableNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(VariableNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "function-argument-name",
-        create: |_config| Box::new(FunctionArgumentNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(FunctionArgumentNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "loop-variable-name",
-        create: |_config| Box::new(LoopVariableNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(LoopVariableNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "enum-name",
-        create: |_config| Box::new(EnumNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(EnumNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "enum-member-name",
-        create: |_config| Box::new(EnumMemberNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(EnumMemberNameRule::new(settings)?)),
     },
     RuleDefinition {
         name: "constant-name",
-        create: |_config| Box::new(ConstantNameRule),
+        options: &[PATTERN_OPTION],
+        create: |_config, settings| Ok(Box::new(ConstantNameRule::new(settings)?)),
+    },
+    RuleDefinition {
+        name: "untyped-declaration",
+        options: STATIC_TYPING_OPTIONS,
+        create: |_config, settings| Ok(Box::new(UntypedDeclarationRule::new(settings)?)),
+    },
+    RuleDefinition {
+        name: "missing-return-type",
+        options: STATIC_TYPING_OPTIONS,
+        create: |_config, settings| Ok(Box::new(MissingReturnTypeRule::new(settings)?)),
+    },
+    RuleDefinition {
+        name: "untyped-parameter",
+        options: STATIC_TYPING_OPTIONS,
+        create: |_config, settings| Ok(Box::new(UntypedParameterRule::new(settings)?)),
+    },
+    RuleDefinition {
+        name: "untyped-loop-variable",
+        options: STATIC_TYPING_OPTIONS,
+        create: |_config, settings| Ok(Box::new(UntypedLoopVariableRule::new(settings)?)),
+    },
+    RuleDefinition {
+        name: "unused-suppression",
+        options: &[],
+        create: |_config, _settings| Ok(Box::new(UnusedSuppressionRule)),
     },
 ];
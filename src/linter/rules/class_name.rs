@@ -1,14 +1,24 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::PASCAL_CASE;
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, PASCAL_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
 
-pub struct ClassNameRule;
+pub struct ClassNameRule {
+    pattern: NamingPattern,
+}
 
 impl ClassNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_class_name(&self, name: &str) -> bool {
-        PASCAL_CASE.is_match(name)
+        self.pattern
+            .is_match(name, |name| PASCAL_CASE.is_match(name))
     }
 }
 
@@ -24,13 +34,21 @@ impl Rule for ClassNameRule {
             let name = get_node_text(&name_node, source_code);
             if !self.is_valid_class_name(name) {
                 let (line, column) = get_line_column(&name_node);
-                issues.push(LintIssue::new(
-                    line,
-                    column,
-                    "class-name".to_string(),
-                    LintSeverity::Error,
-                    format!("Class name '{}' should be in PascalCase format", name),
-                ));
+                let (end_line, end_column) = get_end_line_column(&name_node);
+                issues.push(
+                    LintIssue::new(
+                        line,
+                        column,
+                        "class-name".to_string(),
+                        LintSeverity::Error,
+                        format!(
+                            "Class name '{}' should {}",
+                            name,
+                            self.pattern.describe("be in PascalCase format")
+                        ),
+                    )
+                    .with_end(end_line, end_column),
+                );
             }
         }
 
@@ -1,4 +1,5 @@
-use crate::linter::lib::{get_line_column, get_node_text};
+use crate::linter::fix::{Fix, TextEdit};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
@@ -25,16 +26,32 @@ impl Rule for ComparisonWithItselfRule {
 
                 if left_text == right_text {
                     let (line, column) = get_line_column(&node);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "comparison-with-itself".to_string(),
-                        LintSeverity::Warning,
-                        format!(
-                            "Redundant comparison '{}' - comparing expression with itself",
-                            get_node_text(&node, source_code)
-                        ),
-                    ));
+                    let (end_line, end_column) = get_end_line_column(node);
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "comparison-with-itself".to_string(),
+                            LintSeverity::Warning,
+                            format!(
+                                "Redundant comparison '{}' - comparing expression with itself",
+                                get_node_text(&node, source_code)
+                            ),
+                        )
+                        .with_end(end_line, end_column)
+                        // The comparison always gives the same result, except
+                        // for NaN floats, which are never equal to themselves
+                        .with_fix(Fix::unsafe_fix(vec![
+                            TextEdit::replace_node(
+                                node,
+                                if matches!(op, "==" | "<=" | ">=") {
+                                    "true"
+                                } else {
+                                    "false"
+                                },
+                            ),
+                        ])),
+                    );
                 }
             }
         }
@@ -1,21 +1,32 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::{CONSTANT_CASE, PASCAL_CASE, PRIVATE_CONSTANT_CASE};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{
+    CONSTANT_CASE, NamingPattern, PASCAL_CASE, PRIVATE_CONSTANT_CASE,
+};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
 
-pub struct ConstantNameRule;
+pub struct ConstantNameRule {
+    pattern: NamingPattern,
+}
 
 impl ConstantNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_constant_name(&self, name: &str) -> bool {
-        CONSTANT_CASE.is_match(name) || PRIVATE_CONSTANT_CASE.is_match(name)
+        self.pattern.is_match(name, |name| {
+            CONSTANT_CASE.is_match(name) || PRIVATE_CONSTANT_CASE.is_match(name)
+        })
     }
 
     fn is_valid_load_constant_name(&self, name: &str) -> bool {
         // Load constants can use PascalCase or CONSTANT_CASE
-        PASCAL_CASE.is_match(name)
-            || CONSTANT_CASE.is_match(name)
-            || PRIVATE_CONSTANT_CASE.is_match(name)
+        PASCAL_CASE.is_match(name) || self.is_valid_constant_name(name)
     }
 
     fn is_preload_call(&self, node: &Node, source_code: &str) -> bool {
@@ -52,28 +63,43 @@ impl Rule for ConstantNameRule {
                 // For all load/preload constants, check load naming rules
                 if !self.is_valid_load_constant_name(&name) {
                     let (line, column) = get_line_column(&name_node);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "constant-name".to_string(),
-                        LintSeverity::Error,
-                        format!(
-                            "Preload constant name '{}' should be in PascalCase or CONSTANT_CASE format",
-                            name
-                        ),
-                    ));
+                    let (end_line, end_column) = get_end_line_column(&name_node);
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "constant-name".to_string(),
+                            LintSeverity::Error,
+                            format!(
+                                "Preload constant name '{}' should {}",
+                                name,
+                                self.pattern.describe_with_pascal_case(
+                                    "be in PascalCase or CONSTANT_CASE format"
+                                )
+                            ),
+                        )
+                        .with_end(end_line, end_column),
+                    );
                 }
             } else {
                 // For regular constants, just check regular rules
                 if !self.is_valid_constant_name(&name) {
                     let (line, column) = get_line_column(&name_node);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "constant-name".to_string(),
-                        LintSeverity::Error,
-                        format!("Constant name '{}' should be in CONSTANT_CASE format", name),
-                    ));
+                    let (end_line, end_column) = get_end_line_column(&name_node);
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "constant-name".to_string(),
+                            LintSeverity::Error,
+                            format!(
+                                "Constant name '{}' should {}",
+                                name,
+                                self.pattern.describe("be in CONSTANT_CASE format")
+                            ),
+                        )
+                        .with_end(end_line, end_column),
+                    );
                 }
             }
         }
@@ -0,0 +1,56 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{
+    FUNCTION_KINDS, get_cyclomatic_complexity, get_function_name, get_report_node,
+};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+use tree_sitter::Node;
+
+/// This rule limits the cyclomatic complexity of functions: the number of
+/// independent paths through their code. See the metrics module for the
+/// code that counts as a branch.
+pub struct CyclomaticComplexityRule {
+    max_cyclomatic_complexity: usize,
+}
+
+impl CyclomaticComplexityRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_cyclomatic_complexity: config.max_cyclomatic_complexity,
+        }
+    }
+}
+
+impl Rule for CyclomaticComplexityRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let complexity = get_cyclomatic_complexity(node, source_code);
+        if complexity > self.max_cyclomatic_complexity {
+            let report_node = get_report_node(node);
+            let (line, column) = get_line_column(&report_node);
+            let (end_line, end_column) = get_end_line_column(&report_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "cyclomatic-complexity".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Function '{}' is too complex. Found a cyclomatic complexity of {}, maximum allowed is {}",
+                        get_function_name(node, source_code),
+                        complexity,
+                        self.max_cyclomatic_complexity
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
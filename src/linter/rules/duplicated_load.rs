@@ -1,11 +1,12 @@
-use crate::linter::lib::{get_line_column, get_node_text};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use std::collections::HashMap;
 use tree_sitter::Node;
 
 pub struct DuplicatedLoadRule {
-    pub load_paths: HashMap<String, Vec<(usize, usize)>>,
+    /// Start and end positions of each load call, by path.
+    pub load_paths: HashMap<String, Vec<(usize, usize, usize, usize)>>,
 }
 
 impl Rule for DuplicatedLoadRule {
@@ -25,10 +26,11 @@ impl Rule for DuplicatedLoadRule {
                             if arg_node.kind() == "string" {
                                 let path = get_node_text(&arg_node, source_code);
                                 let (line, column) = get_line_column(&arg_node);
+                                let (end_line, end_column) = get_end_line_column(&arg_node);
                                 self.load_paths
                                     .entry(path.to_string())
                                     .or_insert_with(Vec::new)
-                                    .push((line, column));
+                                    .push((line, column, end_line, end_column));
                             }
                             if !args_cursor.goto_next_sibling() {
                                 break;
@@ -46,17 +48,20 @@ impl Rule for DuplicatedLoadRule {
 
         for (path, locations) in &self.load_paths {
             if locations.len() > 1 {
-                for (line, column) in locations {
-                    issues.push(LintIssue::new(
-                        *line,
-                        *column,
-                        "duplicated-load".to_string(),
-                        LintSeverity::Warning,
-                        format!(
-                            "Duplicated load of '{}'. Consider extracting to a constant.",
-                            path
-                        ),
-                    ));
+                for (line, column, end_line, end_column) in locations {
+                    issues.push(
+                        LintIssue::new(
+                            *line,
+                            *column,
+                            "duplicated-load".to_string(),
+                            LintSeverity::Warning,
+                            format!(
+                                "Duplicated load of '{}'. Consider extracting to a constant.",
+                                path
+                            ),
+                        )
+                        .with_end(*end_line, *end_column),
+                    );
                 }
             }
         }
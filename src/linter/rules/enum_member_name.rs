@@ -1,13 +1,23 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::CONSTANT_CASE;
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{CONSTANT_CASE, NamingPattern};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
-pub struct EnumMemberNameRule;
+pub struct EnumMemberNameRule {
+    pattern: NamingPattern,
+}
 
 impl EnumMemberNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_enum_member_name(&self, name: &str) -> bool {
-        CONSTANT_CASE.is_match(name)
+        self.pattern
+            .is_match(name, |name| CONSTANT_CASE.is_match(name))
     }
 }
 
@@ -33,16 +43,22 @@ impl Rule for EnumMemberNameRule {
                                 && !self.is_valid_enum_member_name(element_name)
                             {
                                 let (line, column) = get_line_column(&element_name_node);
-                                issues.push(LintIssue::new(
-                                    line,
-                                    column,
-                                    "enum-member-name".to_string(),
-                                    LintSeverity::Error,
-                                    format!(
-                                        "Enum element name '{}' should be in CONSTANT_CASE format",
-                                        element_name
-                                    ),
-                                ));
+                                let (end_line, end_column) =
+                                    get_end_line_column(&element_name_node);
+                                issues.push(
+                                    LintIssue::new(
+                                        line,
+                                        column,
+                                        "enum-member-name".to_string(),
+                                        LintSeverity::Error,
+                                        format!(
+                                            "Enum element name '{}' should {}",
+                                            element_name,
+                                            self.pattern.describe("be in CONSTANT_CASE format")
+                                        ),
+                                    )
+                                    .with_end(end_line, end_column),
+                                );
                             }
                         }
                     }
@@ -1,13 +1,23 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::PASCAL_CASE;
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, PASCAL_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
-pub struct EnumNameRule;
+pub struct EnumNameRule {
+    pattern: NamingPattern,
+}
 
 impl EnumNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_enum_name(&self, name: &str) -> bool {
-        PASCAL_CASE.is_match(name)
+        self.pattern
+            .is_match(name, |name| PASCAL_CASE.is_match(name))
     }
 }
 
@@ -24,13 +34,21 @@ impl Rule for EnumNameRule {
             let name = get_node_text(&name_node, source_code);
             if !self.is_valid_enum_name(name) {
                 let (line, column) = get_line_column(&name_node);
-                issues.push(LintIssue::new(
-                    line,
-                    column,
-                    "enum-name".to_string(),
-                    LintSeverity::Error,
-                    format!("Enum name '{}' should be in PascalCase format", name),
-                ));
+                let (end_line, end_column) = get_end_line_column(&name_node);
+                issues.push(
+                    LintIssue::new(
+                        line,
+                        column,
+                        "enum-name".to_string(),
+                        LintSeverity::Error,
+                        format!(
+                            "Enum name '{}' should {}",
+                            name,
+                            self.pattern.describe("be in PascalCase format")
+                        ),
+                    )
+                    .with_end(end_line, end_column),
+                );
             }
         }
 
@@ -1,14 +1,25 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::{PRIVATE_SNAKE_CASE, SNAKE_CASE};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, PRIVATE_SNAKE_CASE, SNAKE_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
 
-pub struct FunctionArgumentNameRule;
+pub struct FunctionArgumentNameRule {
+    pattern: NamingPattern,
+}
 
 impl FunctionArgumentNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_argument_name(&self, name: &str) -> bool {
-        SNAKE_CASE.is_match(name) || PRIVATE_SNAKE_CASE.is_match(name)
+        self.pattern.is_match(name, |name| {
+            SNAKE_CASE.is_match(name) || PRIVATE_SNAKE_CASE.is_match(name)
+        })
     }
 }
 
@@ -43,16 +54,23 @@ impl Rule for FunctionArgumentNameRule {
 
                         if !param_name.is_empty() && !self.is_valid_argument_name(param_name) {
                             let (line, column) = get_line_column(&param_node);
-                            issues.push(LintIssue::new(
-                                line,
-                                column,
-                                "function-argument-name".to_string(),
-                                LintSeverity::Error,
-                                format!(
-                                    "Function argument '{}' should be in snake_case or _private_snake_case format",
-                                    param_name
-                                ),
-                            ));
+                            let (end_line, end_column) = get_end_line_column(&param_node);
+                            issues.push(
+                                LintIssue::new(
+                                    line,
+                                    column,
+                                    "function-argument-name".to_string(),
+                                    LintSeverity::Error,
+                                    format!(
+                                        "Function argument '{}' should {}",
+                                        param_name,
+                                        self.pattern.describe(
+                                            "be in snake_case or _private_snake_case format"
+                                        )
+                                    ),
+                                )
+                                .with_end(end_line, end_column),
+                            );
                         }
                     }
                     if !params_cursor.goto_next_sibling() {
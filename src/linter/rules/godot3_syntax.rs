@@ -0,0 +1,441 @@
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks for Godot 3 syntax that Godot 4 doesn't support anymore,
+/// which is common in projects ported from Godot 3: `yield()`, the `export`,
+/// `onready`, and `tool` keywords, `setget`, networking keywords like `remote`
+/// and `puppet`, and `connect()` calls that name the signal and the method with
+/// strings.
+pub struct Godot3SyntaxRule;
+
+impl Godot3SyntaxRule {
+    fn create_issue(&self, node: &Node, message: String) -> LintIssue {
+        let (line, column) = get_line_column(node);
+        let (end_line, end_column) = get_end_line_column(node);
+        LintIssue::new(
+            line,
+            column,
+            "godot3-syntax".to_string(),
+            LintSeverity::Warning,
+            message,
+        )
+        .with_end(end_line, end_column)
+    }
+
+    fn check_call(&self, node: &Node, source_code: &str) -> Option<LintIssue> {
+        let function_name = get_node_text(&node.child(0)?, source_code);
+        let arguments = get_call_arguments(node);
+        match function_name {
+            "yield" if node.kind() == "call" => {
+                let replacement = get_await_replacement(&arguments, source_code)
+                    .unwrap_or_else(|| "await object.signal_name".to_string());
+                Some(self.create_issue(
+                    node,
+                    format!(
+                        "'yield()' was removed in Godot 4. Use '{}' instead",
+                        replacement
+                    ),
+                ))
+            }
+            "connect" => {
+                let object_prefix = get_object_prefix(node, source_code);
+                let replacement = get_connect_replacement(&arguments, source_code)?;
+                Some(self.create_issue(
+                    node,
+                    format!(
+                        "Connecting signals with strings was removed in Godot 4. Connect a Callable instead: '{}{}'",
+                        object_prefix, replacement
+                    ),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Rule for Godot3SyntaxRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        &[
+            "tool_statement",
+            "export_variable_statement",
+            "onready_variable_statement",
+            "setget",
+            "remote_keyword",
+            "call",
+            "attribute_call",
+        ]
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        match node.kind() {
+            "tool_statement" => issues.push(self.create_issue(
+                node,
+                "The 'tool' keyword was replaced by the '@tool' annotation in Godot 4".to_string(),
+            )),
+            "export_variable_statement" => {
+                if let Some(keyword_node) = node.child(0) {
+                    let replacement = match (
+                        get_export_conversion(node, source_code),
+                        node.child_by_field_name("name"),
+                    ) {
+                        (Some(conversion), Some(name_node)) => format!(
+                            "{} var {}{}",
+                            conversion.annotation,
+                            get_node_text(&name_node, source_code),
+                            conversion
+                                .type_hint
+                                .map(|type_hint| format!(": {}", type_hint))
+                                .unwrap_or_default()
+                        ),
+                        _ => "@export var name: Type".to_string(),
+                    };
+                    issues.push(self.create_issue(
+                        &keyword_node,
+                        format!(
+                            "The 'export' keyword was replaced by the '@export' annotation in Godot 4. Use '{}' instead",
+                            replacement
+                        ),
+                    ));
+                }
+            }
+            "onready_variable_statement" => {
+                if let Some(keyword_node) = node.child(0) {
+                    issues.push(self.create_issue(
+                        &keyword_node,
+                        "The 'onready' keyword was replaced by the '@onready' annotation in Godot 4"
+                            .to_string(),
+                    ));
+                }
+            }
+            // Godot 4 properties also use the setget node for their get and
+            // set blocks
+            "setget" if get_node_text(node, source_code).starts_with("setget") => {
+                issues.push(self.create_issue(
+                    node,
+                    "'setget' was removed in Godot 4. Use 'set' and 'get' blocks after the variable declaration instead, like 'var health: int:' followed by 'set(value):'".to_string(),
+                ));
+            }
+            "remote_keyword" => {
+                let keyword = get_node_text(node, source_code);
+                issues.push(self.create_issue(
+                    node,
+                    format!(
+                        "The '{}' keyword was replaced by the '@rpc' annotation in Godot 4. Use '{}' instead",
+                        keyword,
+                        get_rpc_annotation(keyword)
+                    ),
+                ));
+            }
+            "call" | "attribute_call" => {
+                if let Some(issue) = self.check_call(node, source_code) {
+                    issues.push(issue);
+                }
+            }
+            _ => {}
+        }
+
+        issues
+    }
+}
+
+/// Returns the `@rpc` annotation that replaces a Godot 3 networking keyword.
+///
+/// In Godot 3, peers call `master` functions on the network master, so they
+/// need `"any_peer"` in Godot 4, where only the multiplayer authority can
+/// call RPCs by default.
+pub fn get_rpc_annotation(keyword: &str) -> &'static str {
+    match keyword {
+        "remote" | "master" => "@rpc(\"any_peer\")",
+        "remotesync" | "sync" | "mastersync" => "@rpc(\"any_peer\", \"call_local\")",
+        "puppetsync" => "@rpc(\"call_local\")",
+        _ => "@rpc",
+    }
+}
+
+/// Returns the argument nodes of a call or method call.
+pub fn get_call_arguments<'tree>(call_node: &Node<'tree>) -> Vec<Node<'tree>> {
+    let mut cursor = call_node.walk();
+    let Some(arguments_node) = call_node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "arguments")
+    else {
+        return Vec::new();
+    };
+    let mut arguments_cursor = arguments_node.walk();
+    arguments_node
+        .named_children(&mut arguments_cursor)
+        .filter(|argument| argument.kind() != "comment")
+        .collect()
+}
+
+/// Returns the content of a string literal without its quotes, or None if the
+/// node isn't a string.
+pub fn get_string_content<'a>(node: &Node, source_code: &'a str) -> Option<&'a str> {
+    if !matches!(node.kind(), "string" | "string_name") {
+        return None;
+    }
+    let text = get_node_text(node, source_code).trim_start_matches('&');
+    let quote = text.chars().next()?;
+    text.strip_prefix(quote)?.strip_suffix(quote)
+}
+
+/// Returns the code before the method name of a method call, like `button.`
+/// in `button.connect()`, or an empty string for a plain call.
+fn get_object_prefix<'a>(call_node: &Node, source_code: &'a str) -> &'a str {
+    match call_node.parent() {
+        Some(parent) if call_node.kind() == "attribute_call" && parent.kind() == "attribute" => {
+            &source_code[parent.start_byte()..call_node.start_byte()]
+        }
+        _ => "",
+    }
+}
+
+/// Engine signals that Godot 4 renamed, with their new name.
+const RENAMED_SIGNALS: &[(&str, &str)] = &[("idle_frame", "process_frame")];
+
+/// Returns the Godot 4 code for the arguments of `yield(object, "signal")`,
+/// like `await object.signal`. Waiting for the `completed` signal of a
+/// function call, like `yield(load_level(), "completed")`, waits for a
+/// coroutine, so it becomes `await load_level()`.
+pub fn get_await_replacement(arguments: &[Node], source_code: &str) -> Option<String> {
+    let [object_node, signal_node] = arguments else {
+        return None;
+    };
+    let signal = get_string_content(signal_node, source_code)?;
+    let object = get_node_text(object_node, source_code);
+    if signal == "completed" && is_call(object_node) {
+        return Some(format!("await {}", object));
+    }
+
+    let signal = RENAMED_SIGNALS
+        .iter()
+        .find(|(old_name, _)| *old_name == signal)
+        .map_or(signal, |(_, new_name)| new_name);
+    Some(if object == "self" {
+        format!("await {}", signal)
+    } else {
+        format!("await {}.{}", object, signal)
+    })
+}
+
+/// Returns true if the node is a function call or a method call, like
+/// `load_level()` or `level.load()`.
+fn is_call(node: &Node) -> bool {
+    match node.kind() {
+        "call" => true,
+        "attribute" => node
+            .named_child(node.named_child_count().saturating_sub(1))
+            .is_some_and(|last_child| last_child.kind() == "attribute_call"),
+        _ => false,
+    }
+}
+
+/// Returns the Godot 4 code for the arguments of a Godot 3 connection like
+/// `connect("signal", target, "method", [binds], flags)`, like
+/// `signal.connect(target.method.bind(binds), flags)`. Returns None if the
+/// call doesn't use the Godot 3 signature, where the third argument is the
+/// name of the method.
+pub fn get_connect_replacement(arguments: &[Node], source_code: &str) -> Option<String> {
+    if !(3..=5).contains(&arguments.len()) || arguments[2].kind() != "string" {
+        return None;
+    }
+    let target = get_node_text(&arguments[1], source_code);
+    let method = get_string_content(&arguments[2], source_code)?;
+
+    let mut callable = if target == "self" {
+        method.to_string()
+    } else {
+        format!("{}.{}", target, method)
+    };
+    if let Some(binds_node) = arguments.get(3) {
+        let binds = get_node_text(binds_node, source_code);
+        // Godot 3 takes the bound values as an array, while bind() takes them
+        // as separate arguments
+        match binds
+            .strip_prefix('[')
+            .and_then(|binds| binds.strip_suffix(']'))
+        {
+            Some(values) if values.trim().is_empty() => {}
+            Some(values) => callable = format!("{}.bind({})", callable, values.trim()),
+            None => callable = format!("{}.bindv({})", callable, binds),
+        }
+    }
+
+    let mut connect_arguments = vec![callable];
+    if let Some(flags_node) = arguments.get(4) {
+        connect_arguments.push(get_node_text(flags_node, source_code).to_string());
+    }
+    Some(match get_string_content(&arguments[0], source_code) {
+        Some(signal) => format!("{}.connect({})", signal, connect_arguments.join(", ")),
+        None => {
+            let signal = get_node_text(&arguments[0], source_code).to_string();
+            format!(
+                "connect({})",
+                [vec![signal], connect_arguments].concat().join(", ")
+            )
+        }
+    })
+}
+
+/// The Godot 4 annotation and type hint that replace the `export` keyword
+/// and its hint in a Godot 3 variable declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportConversion {
+    pub annotation: String,
+    pub type_hint: Option<String>,
+}
+
+/// Returns the Godot 4 annotation and type hint for a Godot 3 exported
+/// variable, like `@export_range(0, 10)` and `int` for
+/// `export(int, 0, 10) var`. Returns None for export hints that have no
+/// direct equivalent.
+pub fn get_export_conversion(node: &Node, source_code: &str) -> Option<ExportConversion> {
+    let mut cursor = node.walk();
+    let var_node = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "var")?;
+    let head = source_code[node.start_byte()..var_node.start_byte()]
+        .strip_prefix("export")?
+        .trim();
+    if head.is_empty() {
+        return convert_export_hint(&[]);
+    }
+    let hint = head.strip_prefix('(')?.strip_suffix(')')?;
+    convert_export_hint(&split_arguments(hint))
+}
+
+fn convert_export_hint(arguments: &[&str]) -> Option<ExportConversion> {
+    let is_identifier = |text: &str| {
+        !text.is_empty()
+            && text
+                .chars()
+                .all(|character| character.is_alphanumeric() || character == '_')
+    };
+    let is_number = |text: &str| text.parse::<f64>().is_ok();
+    let is_string = |text: &str| text.starts_with('"') && text.ends_with('"') && text.len() > 1;
+    let conversion = |annotation: String, type_hint: &str| ExportConversion {
+        annotation,
+        type_hint: Some(type_hint.to_string()),
+    };
+
+    Some(match arguments {
+        [] => ExportConversion {
+            annotation: "@export".to_string(),
+            type_hint: None,
+        },
+        [type_hint] if is_identifier(type_hint) => conversion("@export".to_string(), type_hint),
+        ["Array", item_type] if is_identifier(item_type) => {
+            conversion("@export".to_string(), &format!("Array[{}]", item_type))
+        }
+        ["String", "MULTILINE"] => conversion("@export_multiline".to_string(), "String"),
+        ["String", "DIR"] => conversion("@export_dir".to_string(), "String"),
+        ["String", "GLOBAL", "DIR"] => conversion("@export_global_dir".to_string(), "String"),
+        ["String", "FILE", filters @ ..] | ["String", "GLOBAL", "FILE", filters @ ..]
+            if filters.iter().all(|filter| is_string(filter)) =>
+        {
+            let name = if arguments[1] == "GLOBAL" {
+                "@export_global_file"
+            } else {
+                "@export_file"
+            };
+            let annotation = if filters.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}({})", name, filters.join(", "))
+            };
+            conversion(annotation, "String")
+        }
+        ["Color", "RGB"] => conversion("@export_color_no_alpha".to_string(), "Color"),
+        [type_hint @ ("int" | "float"), range @ ..]
+            if (1..=3).contains(&range.len()) && range.iter().all(|value| is_number(value)) =>
+        {
+            // Godot 3 accepts only the maximum value, with 0 as the minimum
+            let range = if range.len() == 1 {
+                format!("0, {}", range[0])
+            } else {
+                range.join(", ")
+            };
+            conversion(format!("@export_range({})", range), type_hint)
+        }
+        ["int", "FLAGS", names @ ..]
+            if !names.is_empty() && names.iter().all(|name| is_string(name)) =>
+        {
+            conversion(format!("@export_flags({})", names.join(", ")), "int")
+        }
+        [type_hint @ ("int" | "String"), names @ ..]
+            if !names.is_empty() && names.iter().all(|name| is_string(name)) =>
+        {
+            conversion(format!("@export_enum({})", names.join(", ")), type_hint)
+        }
+        _ => return None,
+    })
+}
+
+/// Splits a list of arguments on the commas that aren't inside of strings,
+/// brackets, or parentheses.
+fn split_arguments(text: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, character) in text.char_indices() {
+        match (quote, character) {
+            (Some(open_quote), _) if character == open_quote => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(character),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                arguments.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = text[start..].trim();
+    if !last.is_empty() {
+        arguments.push(last);
+    }
+    arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_export_hint() {
+        let convert = |hint: &str| {
+            convert_export_hint(&split_arguments(hint))
+                .map(|conversion| (conversion.annotation, conversion.type_hint))
+        };
+        let some = |annotation: &str, type_hint: &str| {
+            Some((annotation.to_string(), Some(type_hint.to_string())))
+        };
+
+        assert_eq!(convert("int"), some("@export", "int"));
+        assert_eq!(convert("Array, int"), some("@export", "Array[int]"));
+        assert_eq!(convert("int, 10"), some("@export_range(0, 10)", "int"));
+        assert_eq!(
+            convert("float, -1.0, 1.0, 0.1"),
+            some("@export_range(-1.0, 1.0, 0.1)", "float")
+        );
+        assert_eq!(
+            convert("String, FILE, \"*.png, *.jpg\""),
+            some("@export_file(\"*.png, *.jpg\")", "String")
+        );
+        assert_eq!(
+            convert("int, \"Small\", \"Big\""),
+            some("@export_enum(\"Small\", \"Big\")", "int")
+        );
+        assert_eq!(
+            convert("int, FLAGS, \"Fire\", \"Water\""),
+            some("@export_flags(\"Fire\", \"Water\")", "int")
+        );
+        assert_eq!(convert("float, EXP, 100, 1000"), None);
+    }
+}
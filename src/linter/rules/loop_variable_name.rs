@@ -1,14 +1,24 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::SNAKE_CASE;
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, SNAKE_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
 
-pub struct LoopVariableNameRule;
+pub struct LoopVariableNameRule {
+    pattern: NamingPattern,
+}
 
 impl LoopVariableNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_loop_variable_name(&self, name: &str) -> bool {
-        SNAKE_CASE.is_match(name)
+        self.pattern
+            .is_match(name, |name| SNAKE_CASE.is_match(name))
     }
 }
 
@@ -39,16 +49,21 @@ impl Rule for LoopVariableNameRule {
 
             if !variable_name.is_empty() && !self.is_valid_loop_variable_name(variable_name) {
                 let (line, column) = get_line_column(&left_node);
-                issues.push(LintIssue::new(
-                    line,
-                    column,
-                    "loop-variable-name".to_string(),
-                    LintSeverity::Error,
-                    format!(
-                        "Loop variable '{}' should be in snake_case format",
-                        variable_name
-                    ),
-                ));
+                let (end_line, end_column) = get_end_line_column(&left_node);
+                issues.push(
+                    LintIssue::new(
+                        line,
+                        column,
+                        "loop-variable-name".to_string(),
+                        LintSeverity::Error,
+                        format!(
+                            "Loop variable '{}' should {}",
+                            variable_name,
+                            self.pattern.describe("be in snake_case format")
+                        ),
+                    )
+                    .with_end(end_line, end_column),
+                );
             }
         }
 
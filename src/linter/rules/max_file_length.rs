@@ -0,0 +1,41 @@
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+
+/// This rule limits the number of lines of a file.
+pub struct MaxFileLengthRule {
+    max_file_length: usize,
+}
+
+impl MaxFileLengthRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_file_length: config.max_file_length,
+        }
+    }
+}
+
+impl Rule for MaxFileLengthRule {
+    fn check_source(&mut self, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let line_count = source_code.lines().count();
+        if line_count > self.max_file_length {
+            // We report the issue on the first line over the limit
+            issues.push(
+                LintIssue::new(
+                    self.max_file_length + 1,
+                    1,
+                    "max-file-length".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "File is too long. Found {} lines, maximum allowed is {}",
+                        line_count, self.max_file_length
+                    ),
+                )
+                .with_end(line_count, 1),
+            );
+        }
+
+        issues
+    }
+}
@@ -0,0 +1,53 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{FUNCTION_KINDS, count_lines, get_function_name, get_report_node};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+use tree_sitter::Node;
+
+/// This rule limits the number of lines of functions, from their signature to
+/// the end of their body.
+pub struct MaxFunctionLengthRule {
+    max_function_length: usize,
+}
+
+impl MaxFunctionLengthRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_function_length: config.max_function_length,
+        }
+    }
+}
+
+impl Rule for MaxFunctionLengthRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let length = count_lines(node);
+        if length > self.max_function_length {
+            let report_node = get_report_node(node);
+            let (line, column) = get_line_column(&report_node);
+            let (end_line, end_column) = get_end_line_column(&report_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "max-function-length".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Function '{}' is too long. Found {} lines, maximum allowed is {}",
+                        get_function_name(node, source_code),
+                        length,
+                        self.max_function_length
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
@@ -18,21 +18,31 @@ impl Rule for MaxLineLengthRule {
         let mut issues = Vec::new();
 
         for (line_number, line) in source_code.lines().enumerate() {
-            let display_width = line
-                .chars()
-                .fold(0, |acc, ch| if ch == '\t' { acc + 4 } else { acc + 1 });
+            // Columns are byte offsets like in other rules, so we find the
+            // byte where the display width goes past the limit
+            let mut display_width = 0;
+            let mut start_column = None;
+            for (byte_index, ch) in line.char_indices() {
+                display_width += if ch == '\t' { 4 } else { 1 };
+                if display_width > self.config.max_line_length && start_column.is_none() {
+                    start_column = Some(byte_index + 1);
+                }
+            }
 
-            if display_width > self.config.max_line_length {
-                issues.push(LintIssue::new(
-                    line_number + 1,
-                    self.config.max_line_length + 1,
-                    "max-line-length".to_string(),
-                    LintSeverity::Warning,
-                    format!(
-                        "Line is too long. Found {} characters, maximum allowed is {}",
-                        display_width, self.config.max_line_length
-                    ),
-                ));
+            if let Some(start_column) = start_column {
+                issues.push(
+                    LintIssue::new(
+                        line_number + 1,
+                        start_column,
+                        "max-line-length".to_string(),
+                        LintSeverity::Warning,
+                        format!(
+                            "Line is too long. Found {} characters, maximum allowed is {}",
+                            display_width, self.config.max_line_length
+                        ),
+                    )
+                    .with_end(line_number + 1, line.len() + 1),
+                );
             }
         }
 
@@ -0,0 +1,56 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{
+    FUNCTION_KINDS, get_function_name, get_nesting_depth, get_report_node,
+};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+use tree_sitter::Node;
+
+/// This rule limits how deeply blocks are nested in functions. Statements
+/// directly in the body of a function have a depth of 0, and each if, elif,
+/// else, for, while, or match pattern section adds a level.
+pub struct MaxNestingDepthRule {
+    max_nesting_depth: usize,
+}
+
+impl MaxNestingDepthRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_nesting_depth: config.max_nesting_depth,
+        }
+    }
+}
+
+impl Rule for MaxNestingDepthRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let depth = get_nesting_depth(node);
+        if depth > self.max_nesting_depth {
+            let report_node = get_report_node(node);
+            let (line, column) = get_line_column(&report_node);
+            let (end_line, end_column) = get_end_line_column(&report_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "max-nesting-depth".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Function '{}' is nested too deeply. Found a depth of {}, maximum allowed is {}",
+                        get_function_name(node, source_code),
+                        depth,
+                        self.max_nesting_depth
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
@@ -0,0 +1,54 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{
+    FUNCTION_KINDS, count_parameters, get_function_name, get_report_node,
+};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+use tree_sitter::Node;
+
+/// This rule limits the number of parameters of functions.
+pub struct MaxParametersRule {
+    max_parameters: usize,
+}
+
+impl MaxParametersRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_parameters: config.max_parameters,
+        }
+    }
+}
+
+impl Rule for MaxParametersRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let parameters = count_parameters(node);
+        if parameters > self.max_parameters {
+            let report_node = get_report_node(node);
+            let (line, column) = get_line_column(&report_node);
+            let (end_line, end_column) = get_end_line_column(&report_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "max-parameters".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Function '{}' has too many parameters. Found {}, maximum allowed is {}",
+                        get_function_name(node, source_code),
+                        parameters,
+                        self.max_parameters
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
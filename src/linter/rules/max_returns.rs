@@ -0,0 +1,52 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{FUNCTION_KINDS, count_returns, get_function_name, get_report_node};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity, LinterConfig};
+use tree_sitter::Node;
+
+/// This rule limits the number of return statements in functions.
+pub struct MaxReturnsRule {
+    max_returns: usize,
+}
+
+impl MaxReturnsRule {
+    pub fn new(config: &LinterConfig) -> Self {
+        Self {
+            max_returns: config.max_returns,
+        }
+    }
+}
+
+impl Rule for MaxReturnsRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let returns = count_returns(node);
+        if returns > self.max_returns {
+            let report_node = get_report_node(node);
+            let (line, column) = get_line_column(&report_node);
+            let (end_line, end_column) = get_end_line_column(&report_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "max-returns".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Function '{}' has too many return statements. Found {}, maximum allowed is {}",
+                        get_function_name(node, source_code),
+                        returns,
+                        self.max_returns
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
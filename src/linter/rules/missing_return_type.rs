@@ -0,0 +1,57 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::metrics::{FUNCTION_KINDS, get_function_name, get_report_node};
+use crate::linter::rule_config::RuleSettings;
+use crate::linter::rules::Rule;
+use crate::linter::static_typing::TypingExemptions;
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks that functions, constructors, and lambdas declare their
+/// return type with `->`.
+pub struct MissingReturnTypeRule {
+    exemptions: TypingExemptions,
+}
+
+impl MissingReturnTypeRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            exemptions: TypingExemptions::from_settings(settings)?,
+        })
+    }
+}
+
+impl Rule for MissingReturnTypeRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let mut cursor = node.walk();
+        let has_return_type = node.child_by_field_name("return_type").is_some()
+            || node.children(&mut cursor).any(|child| child.kind() == "->");
+        if has_return_type || self.exemptions.is_exempt(node, source_code) {
+            return issues;
+        }
+
+        let report_node = get_report_node(node);
+        let (line, column) = get_line_column(&report_node);
+        let (end_line, end_column) = get_end_line_column(&report_node);
+        issues.push(
+            LintIssue::new(
+                line,
+                column,
+                "missing-return-type".to_string(),
+                LintSeverity::Warning,
+                format!(
+                    "Function '{}' has no return type. Add one with '-> Type', or '-> void' if it returns nothing",
+                    get_function_name(node, source_code)
+                ),
+            )
+            .with_end(end_line, end_column),
+        );
+
+        issues
+    }
+}
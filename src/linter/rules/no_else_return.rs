@@ -1,39 +1,11 @@
-use crate::linter::lib::get_line_column;
+use crate::linter::fix::{Fix, TextEdit};
+use crate::linter::lib::{body_always_exits, get_end_line_column, get_line_column};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
 
 pub struct NoElseReturnRule;
 
-impl NoElseReturnRule {
-    fn body_ends_with_return(&self, body_node: &Node, _source_code: &str) -> bool {
-        let mut cursor = body_node.walk();
-        let mut last_statement = None;
-
-        if cursor.goto_first_child() {
-            loop {
-                let child_node = cursor.node();
-                // Skip whitespace and comments
-                if !matches!(
-                    child_node.kind(),
-                    "_newline" | "_indent" | "_dedent" | "comment"
-                ) {
-                    last_statement = Some(child_node);
-                }
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
-            }
-        }
-
-        if let Some(last_stmt) = last_statement {
-            return last_stmt.kind() == "return_statement";
-        }
-
-        false
-    }
-}
-
 impl Rule for NoElseReturnRule {
     fn get_target_ast_nodes(&self) -> &[&str] {
         &["if_statement"]
@@ -44,10 +16,14 @@ impl Rule for NoElseReturnRule {
 
         let mut if_body_ends_with_return = false;
         if let Some(body_node) = node.child_by_field_name("body") {
-            if_body_ends_with_return = self.body_ends_with_return(&body_node, source_code);
+            if_body_ends_with_return =
+                body_always_exits(&body_node, &["return_statement"], source_code);
         }
 
         let mut all_branches_return = if_body_ends_with_return;
+        // Only the first elif can become an if without changing the behavior
+        // of the code, as the following ones depend on the previous branches
+        let mut is_first_elif = true;
 
         let mut stmt_cursor = node.walk();
         if stmt_cursor.goto_first_child() {
@@ -56,31 +32,47 @@ impl Rule for NoElseReturnRule {
                 if child_node.kind() == "elif_clause" {
                     if if_body_ends_with_return {
                         let (line, column) = get_line_column(&child_node);
-                        issues.push(LintIssue::new(
+                        let (end_line, end_column) = get_keyword_end(&child_node);
+                        let mut issue = LintIssue::new(
                             line,
                             column,
                             "no-else-return".to_string(),
                             LintSeverity::Warning,
                             "Unnecessary 'elif' after 'if' block that ends with 'return'. Use 'if' instead".to_string(),
-                        ));
+                        )
+                        .with_end(end_line, end_column);
+                        if is_first_elif && let Some(keyword_node) = child_node.child(0) {
+                            issue = issue.with_fix(Fix::safe(vec![TextEdit::replace_node(
+                                &keyword_node,
+                                "if",
+                            )]));
+                        }
+                        issues.push(issue);
                     }
+                    is_first_elif = false;
 
                     if let Some(elif_body) = child_node.child_by_field_name("body") {
-                        if !self.body_ends_with_return(&elif_body, source_code) {
+                        if !body_always_exits(&elif_body, &["return_statement"], source_code) {
                             all_branches_return = false;
                         }
                     }
                 } else if child_node.kind() == "else_clause" {
                     let (line, column) = get_line_column(&child_node);
+                    let (end_line, end_column) = get_keyword_end(&child_node);
                     if all_branches_return {
-                        issues.push(LintIssue::new(
+                        let mut issue = LintIssue::new(
                             line,
                             column,
                             "no-else-return".to_string(),
                             LintSeverity::Warning,
                             "Unnecessary 'else' after 'if'/'elif' blocks that end with 'return'"
                                 .to_string(),
-                        ));
+                        )
+                        .with_end(end_line, end_column);
+                        if let Some(fix) = get_else_fix(&child_node, source_code) {
+                            issue = issue.with_fix(fix);
+                        }
+                        issues.push(issue);
                     }
                 }
                 if !stmt_cursor.goto_next_sibling() {
@@ -92,3 +84,62 @@ impl Rule for NoElseReturnRule {
         issues
     }
 }
+
+/// Returns the end position of the `elif` or `else` keyword that starts a
+/// clause, so that we only highlight the keyword and not the whole block.
+fn get_keyword_end(clause_node: &Node) -> (usize, usize) {
+    match clause_node.child(0) {
+        Some(keyword_node) => get_end_line_column(&keyword_node),
+        None => get_end_line_column(clause_node),
+    }
+}
+
+/// Returns a fix that removes the `else:` line and moves the else block's
+/// statements one indentation level to the left. Returns None if the block is
+/// on the same line as the `else` keyword or if there's a comment after it.
+///
+/// The fix is unsafe if the block contains strings spanning multiple lines,
+/// as changing their indentation would change their content.
+fn get_else_fix(else_node: &Node, source_code: &str) -> Option<Fix> {
+    let body_node = else_node.child_by_field_name("body")?;
+    if body_node.start_position().row == else_node.start_position().row {
+        return None;
+    }
+
+    let else_line_start = else_node.start_byte() - else_node.start_position().column;
+    let else_indent = &source_code[else_line_start..else_node.start_byte()];
+    let else_line_end = else_line_start + source_code[else_line_start..].find('\n')?;
+    let else_line = source_code[else_node.start_byte()..else_line_end].trim_end();
+    if !else_indent.trim().is_empty() || else_line != "else:" {
+        return None;
+    }
+
+    let body_line_start = body_node.start_byte() - body_node.start_position().column;
+    let body_indent = &source_code[body_line_start..body_node.start_byte()];
+    let dedented_lines: Vec<String> = source_code[else_line_end + 1..body_node.end_byte()]
+        .split('\n')
+        .map(|line| match line.strip_prefix(body_indent) {
+            Some(rest) => format!("{}{}", else_indent, rest),
+            None => line.to_string(),
+        })
+        .collect();
+
+    let edit = TextEdit::replace(
+        else_line_start..body_node.end_byte(),
+        dedented_lines.join("\n"),
+    );
+    if contains_multiline_string(&body_node) {
+        Some(Fix::unsafe_fix(vec![edit]))
+    } else {
+        Some(Fix::safe(vec![edit]))
+    }
+}
+
+fn contains_multiline_string(node: &Node) -> bool {
+    if node.kind() == "string" && node.start_position().row != node.end_position().row {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| contains_multiline_string(&child))
+}
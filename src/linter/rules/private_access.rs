@@ -1,4 +1,4 @@
-use crate::linter::lib::{get_line_column, get_node_text};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
@@ -27,13 +27,17 @@ impl Rule for PrivateAccessRule {
                             && object_name != "self"
                         {
                             let (line, column) = get_line_column(&method_name_node);
-                            issues.push(LintIssue::new(
-                                line,
-                                column,
-                                "private-access".to_string(),
-                                LintSeverity::Error,
-                                format!("Private method '{}' should not be called from outside its class", method_name),
-                            ));
+                            let (end_line, end_column) = get_end_line_column(&method_name_node);
+                            issues.push(
+                                LintIssue::new(
+                                    line,
+                                    column,
+                                    "private-access".to_string(),
+                                    LintSeverity::Error,
+                                    format!("Private method '{}' should not be called from outside its class", method_name),
+                                )
+                                .with_end(end_line, end_column),
+                            );
                         }
                     }
                 } else if method_node.kind() == "identifier" {
@@ -43,13 +47,17 @@ impl Rule for PrivateAccessRule {
                         && object_name != "self"
                     {
                         let (line, column) = get_line_column(&method_node);
-                        issues.push(LintIssue::new(
-                            line,
-                            column,
-                            "private-access".to_string(),
-                            LintSeverity::Error,
-                            format!("Private variable '{}' should not be accessed from outside its class", method_name),
-                        ));
+                        let (end_line, end_column) = get_end_line_column(&method_node);
+                        issues.push(
+                            LintIssue::new(
+                                line,
+                                column,
+                                "private-access".to_string(),
+                                LintSeverity::Error,
+                                format!("Private variable '{}' should not be accessed from outside its class", method_name),
+                            )
+                            .with_end(end_line, end_column),
+                        );
                     }
                 }
             }
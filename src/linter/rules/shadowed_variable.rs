@@ -0,0 +1,183 @@
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::rules::Rule;
+use crate::linter::scope::{ScopeTree, Symbol};
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// Built-in classes that inherit from Node2D.
+const NODE_2D_CLASSES: &[&str] = &[
+    "Node2D",
+    "AnimatableBody2D",
+    "AnimatedSprite2D",
+    "Area2D",
+    "AudioListener2D",
+    "AudioStreamPlayer2D",
+    "Bone2D",
+    "Camera2D",
+    "CanvasGroup",
+    "CharacterBody2D",
+    "CollisionObject2D",
+    "CollisionPolygon2D",
+    "CollisionShape2D",
+    "CPUParticles2D",
+    "DirectionalLight2D",
+    "GPUParticles2D",
+    "KinematicBody2D",
+    "Light2D",
+    "LightOccluder2D",
+    "Line2D",
+    "Marker2D",
+    "MeshInstance2D",
+    "MultiMeshInstance2D",
+    "NavigationLink2D",
+    "NavigationObstacle2D",
+    "NavigationRegion2D",
+    "Parallax2D",
+    "ParallaxLayer",
+    "Path2D",
+    "PathFollow2D",
+    "PhysicalBone2D",
+    "PhysicsBody2D",
+    "PointLight2D",
+    "Polygon2D",
+    "Position2D",
+    "RayCast2D",
+    "RemoteTransform2D",
+    "RigidBody2D",
+    "ShapeCast2D",
+    "Skeleton2D",
+    "Sprite",
+    "Sprite2D",
+    "StaticBody2D",
+    "TileMap",
+    "TileMapLayer",
+    "TouchScreenButton",
+    "VisibleOnScreenEnabler2D",
+    "VisibleOnScreenNotifier2D",
+];
+
+/// Properties of Node2D and its base classes that scripts commonly hide by
+/// mistake.
+const NODE_2D_PROPERTIES: &[&str] = &[
+    "name",
+    "position",
+    "rotation",
+    "scale",
+    "skew",
+    "transform",
+    "global_position",
+    "global_rotation",
+    "global_scale",
+    "global_transform",
+    "visible",
+    "modulate",
+];
+
+/// This rule checks for local variables, function arguments, and loop
+/// variables that hide a class member, a variable of an enclosing function,
+/// or a common built-in property of the class the script extends, like
+/// Godot's SHADOWED_VARIABLE and SHADOWED_VARIABLE_BASE_CLASS warnings.
+pub struct ShadowedVariableRule;
+
+impl ShadowedVariableRule {
+    /// Returns the name of the class that a script or inner class extends.
+    fn get_extended_class<'a>(&self, class_node: &Node, source_code: &'a str) -> Option<&'a str> {
+        let extends_node = if class_node.kind() == "class_definition" {
+            class_node.child_by_field_name("extends")
+        } else {
+            self.find_extends_statement(class_node)
+        }?;
+        let text = get_node_text(&extends_node, source_code);
+        Some(text.trim_start_matches("extends").trim())
+    }
+
+    fn find_extends_statement<'tree>(&self, source_node: &Node<'tree>) -> Option<Node<'tree>> {
+        let mut cursor = source_node.walk();
+        for statement in source_node.named_children(&mut cursor) {
+            match statement.kind() {
+                "extends_statement" => return Some(statement),
+                // The extends keyword can also follow class_name on the same
+                // line
+                "class_name_statement" => {
+                    let mut statement_cursor = statement.walk();
+                    let extends_node = statement
+                        .named_children(&mut statement_cursor)
+                        .find(|child| child.kind() == "extends_statement");
+                    if extends_node.is_some() {
+                        return extends_node;
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn shadows_built_in_property(
+        &self,
+        scopes: &ScopeTree,
+        symbol: &Symbol,
+        source_code: &str,
+    ) -> Option<&'static str> {
+        let class_scope = scopes.get_class_scope(symbol.scope);
+        let extended_class =
+            self.get_extended_class(&scopes.scope(class_scope).node, source_code)?;
+        if NODE_2D_CLASSES.contains(&extended_class)
+            && NODE_2D_PROPERTIES.contains(&symbol.name.as_str())
+        {
+            Some("Node2D")
+        } else {
+            None
+        }
+    }
+}
+
+impl Rule for ShadowedVariableRule {
+    fn check_scopes(&mut self, scopes: &ScopeTree, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        for (id, symbol) in scopes.symbols().iter().enumerate() {
+            if !symbol.kind.is_local() {
+                continue;
+            }
+
+            let shadowed = if let Some(shadowed_id) = scopes.find_shadowed_symbol(id) {
+                let shadowed_symbol = scopes.symbol(shadowed_id);
+                let (shadowed_line, _) = get_line_column(&shadowed_symbol.name_node);
+                format!(
+                    "the {} declared on line {}",
+                    shadowed_symbol.kind.describe(),
+                    shadowed_line
+                )
+            } else if let Some(class_name) =
+                self.shadows_built_in_property(scopes, symbol, source_code)
+            {
+                format!("the built-in {} property", class_name)
+            } else {
+                continue;
+            };
+
+            let description = symbol.kind.describe();
+            let (line, column) = get_line_column(&symbol.name_node);
+            let (end_line, end_column) = get_end_line_column(&symbol.name_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "shadowed-variable".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "{}{} '{}' shadows {}",
+                        description[..1].to_uppercase(),
+                        &description[1..],
+                        symbol.name,
+                        shadowed
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
@@ -1,13 +1,23 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::SNAKE_CASE;
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, SNAKE_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
-pub struct SignalNameRule;
+pub struct SignalNameRule {
+    pattern: NamingPattern,
+}
 
 impl SignalNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_signal_name(&self, name: &str) -> bool {
-        SNAKE_CASE.is_match(name)
+        self.pattern
+            .is_match(name, |name| SNAKE_CASE.is_match(name))
     }
 }
 
@@ -23,13 +33,21 @@ impl Rule for SignalNameRule {
             let name = get_node_text(&name_node, source_code);
             if !self.is_valid_signal_name(name) {
                 let (line, column) = get_line_column(&name_node);
-                issues.push(LintIssue::new(
-                    line,
-                    column,
-                    "signal-name".to_string(),
-                    LintSeverity::Error,
-                    format!("Signal name '{}' should be in snake_case format", name),
-                ));
+                let (end_line, end_column) = get_end_line_column(&name_node);
+                issues.push(
+                    LintIssue::new(
+                        line,
+                        column,
+                        "signal-name".to_string(),
+                        LintSeverity::Error,
+                        format!(
+                            "Signal name '{}' should {}",
+                            name,
+                            self.pattern.describe("be in snake_case format")
+                        ),
+                    )
+                    .with_end(end_line, end_column),
+                );
             }
         }
 
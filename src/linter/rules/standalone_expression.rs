@@ -1,4 +1,4 @@
-use crate::linter::lib::{get_line_column, get_node_text};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
@@ -24,17 +24,21 @@ impl Rule for StandaloneExpressionRule {
                     "binary_operator" | "integer" | "float" | "string" | "true" | "false" | "null"
                 ) {
                     let (line, column) = get_line_column(&expr_child);
+                    let (end_line, end_column) = get_end_line_column(&expr_child);
                     let expr_text = get_node_text(&expr_child, source_code);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "standalone-expression".to_string(),
-                        LintSeverity::Warning,
-                        format!(
-                            "Standalone expression '{}' is not assigned or used, the line may have no effect",
-                            expr_text
-                        ),
-                    ));
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "standalone-expression".to_string(),
+                            LintSeverity::Warning,
+                            format!(
+                                "Standalone expression '{}' is not assigned or used, the line may have no effect",
+                                expr_text
+                            ),
+                        )
+                        .with_end(end_line, end_column),
+                    );
                 }
             }
         }
@@ -1,4 +1,5 @@
-use crate::linter::lib::get_line_column;
+use crate::linter::fix::{Fix, TextEdit};
+use crate::linter::lib::{get_end_line_column, get_line_column};
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
@@ -9,7 +10,7 @@ impl Rule for UnnecessaryPassRule {
         &["body", "class_body"]
     }
 
-    fn check_node(&mut self, node: &Node, _source_code: &str) -> Vec<LintIssue> {
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
         let mut issues = Vec::new();
         let mut has_other_statements = false;
         let mut pass_nodes = Vec::new();
@@ -35,13 +36,22 @@ impl Rule for UnnecessaryPassRule {
         if has_other_statements {
             for pass_node in pass_nodes {
                 let (line, column) = get_line_column(&pass_node);
-                issues.push(LintIssue::new(
-                    line,
-                    column,
-                    "unnecessary-pass".to_string(),
-                    LintSeverity::Warning,
-                    "Unnecessary 'pass' statement when other statements are present".to_string(),
-                ));
+                let (end_line, end_column) = get_end_line_column(&pass_node);
+                issues.push(
+                    LintIssue::new(
+                        line,
+                        column,
+                        "unnecessary-pass".to_string(),
+                        LintSeverity::Warning,
+                        "Unnecessary 'pass' statement when other statements are present"
+                            .to_string(),
+                    )
+                    .with_end(end_line, end_column)
+                    .with_fix(Fix::safe(vec![TextEdit::delete_statement(
+                        &pass_node,
+                        source_code,
+                    )])),
+                );
             }
         }
 
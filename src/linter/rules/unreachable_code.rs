@@ -0,0 +1,61 @@
+use crate::linter::lib::{
+    JUMP_STATEMENTS, get_end_line_column, get_line_column, get_statements, statement_always_exits,
+};
+use crate::linter::rules::Rule;
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks for statements that can never run because a statement
+/// before them in the same block always returns, breaks, or continues: a
+/// `return`, `break`, or `continue` statement, an if or match statement where
+/// every branch does, or a `while true` loop without a `break`.
+pub struct UnreachableCodeRule;
+
+impl UnreachableCodeRule {
+    fn get_message(&self, exit_node: &Node) -> String {
+        match exit_node.kind() {
+            "return_statement" => "Unreachable code after 'return'".to_string(),
+            "break_statement" => "Unreachable code after 'break'".to_string(),
+            "continue_statement" => "Unreachable code after 'continue'".to_string(),
+            "while_statement" => "Unreachable code after infinite 'while' loop".to_string(),
+            kind => format!(
+                "Unreachable code: every branch of the '{}' statement above returns or exits the loop",
+                kind.trim_end_matches("_statement")
+            ),
+        }
+    }
+}
+
+impl Rule for UnreachableCodeRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        &["body"]
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let statements = get_statements(node);
+        let Some(exit_index) = statements
+            .iter()
+            .position(|statement| statement_always_exits(statement, JUMP_STATEMENTS, source_code))
+        else {
+            return Vec::new();
+        };
+        let unreachable = &statements[exit_index + 1..];
+        let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) else {
+            return Vec::new();
+        };
+
+        // We report all the unreachable statements of the block as one issue
+        let (line, column) = get_line_column(first);
+        let (end_line, end_column) = get_end_line_column(last);
+        vec![
+            LintIssue::new(
+                line,
+                column,
+                "unreachable-code".to_string(),
+                LintSeverity::Warning,
+                self.get_message(&statements[exit_index]),
+            )
+            .with_end(end_line, end_column),
+        ]
+    }
+}
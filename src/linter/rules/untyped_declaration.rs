@@ -0,0 +1,70 @@
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::metrics::FUNCTION_KINDS;
+use crate::linter::rule_config::RuleSettings;
+use crate::linter::rules::Rule;
+use crate::linter::static_typing::{TypingExemptions, has_inferred_type};
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks that class and local variables have a static type, either
+/// written after a colon or inferred from their value with `:=`.
+pub struct UntypedDeclarationRule {
+    exemptions: TypingExemptions,
+}
+
+impl UntypedDeclarationRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            exemptions: TypingExemptions::from_settings(settings)?,
+        })
+    }
+
+    fn is_local(&self, node: &Node) -> bool {
+        let mut current = node.parent();
+        while let Some(ancestor) = current {
+            if FUNCTION_KINDS.contains(&ancestor.kind()) {
+                return true;
+            }
+            current = ancestor.parent();
+        }
+        false
+    }
+}
+
+impl Rule for UntypedDeclarationRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        &["variable_statement"]
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        if node.child_by_field_name("type").is_some()
+            || has_inferred_type(node)
+            || self.exemptions.is_exempt(node, source_code)
+        {
+            return issues;
+        }
+
+        if let Some(name_node) = node.child_by_field_name("name") {
+            let (line, column) = get_line_column(&name_node);
+            let (end_line, end_column) = get_end_line_column(&name_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "untyped-declaration".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "{} variable '{}' has no static type. Add a type with ': Type' or infer it with ':='",
+                        if self.is_local(node) { "Local" } else { "Class" },
+                        get_node_text(&name_node, source_code)
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
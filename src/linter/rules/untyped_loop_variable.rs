@@ -0,0 +1,58 @@
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::rule_config::RuleSettings;
+use crate::linter::rules::Rule;
+use crate::linter::static_typing::TypingExemptions;
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks that loop variables have a static type, like
+/// `for i: int in range(10)`.
+pub struct UntypedLoopVariableRule {
+    exemptions: TypingExemptions,
+}
+
+impl UntypedLoopVariableRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            exemptions: TypingExemptions::from_settings(settings)?,
+        })
+    }
+}
+
+impl Rule for UntypedLoopVariableRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        &["for_statement"]
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let Some(left_node) = node.child_by_field_name("left") else {
+            return issues;
+        };
+        if left_node.kind() != "identifier"
+            || node.child_by_field_name("type").is_some()
+            || self.exemptions.is_exempt(node, source_code)
+        {
+            return issues;
+        }
+
+        let (line, column) = get_line_column(&left_node);
+        let (end_line, end_column) = get_end_line_column(&left_node);
+        issues.push(
+            LintIssue::new(
+                line,
+                column,
+                "untyped-loop-variable".to_string(),
+                LintSeverity::Warning,
+                format!(
+                    "Loop variable '{}' has no static type. Add a type with ': Type'",
+                    get_node_text(&left_node, source_code)
+                ),
+            )
+            .with_end(end_line, end_column),
+        );
+
+        issues
+    }
+}
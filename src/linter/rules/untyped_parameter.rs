@@ -0,0 +1,72 @@
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::metrics::{FUNCTION_KINDS, get_function_name};
+use crate::linter::rule_config::RuleSettings;
+use crate::linter::rules::Rule;
+use crate::linter::static_typing::{TypingExemptions, has_inferred_type};
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks that the parameters of functions, constructors, and
+/// lambdas have a static type, either written after a colon or inferred from
+/// their default value with `:=`.
+pub struct UntypedParameterRule {
+    exemptions: TypingExemptions,
+}
+
+impl UntypedParameterRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            exemptions: TypingExemptions::from_settings(settings)?,
+        })
+    }
+}
+
+impl Rule for UntypedParameterRule {
+    fn get_target_ast_nodes(&self) -> &[&str] {
+        FUNCTION_KINDS
+    }
+
+    fn check_node(&mut self, node: &Node, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        let Some(parameters_node) = node.child_by_field_name("parameters") else {
+            return issues;
+        };
+        if self.exemptions.is_exempt(node, source_code) {
+            return issues;
+        }
+
+        let mut cursor = parameters_node.walk();
+        for parameter_node in parameters_node.named_children(&mut cursor) {
+            let name_node = match parameter_node.kind() {
+                "identifier" => parameter_node,
+                "default_parameter" if !has_inferred_type(&parameter_node) => {
+                    match parameter_node.child(0) {
+                        Some(name_node) => name_node,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            let (line, column) = get_line_column(&name_node);
+            let (end_line, end_column) = get_end_line_column(&name_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "untyped-parameter".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Parameter '{}' of function '{}' has no static type. Add a type with ': Type' or infer it from its default value with ':='",
+                        get_node_text(&name_node, source_code),
+                        get_function_name(node, source_code)
+                    ),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
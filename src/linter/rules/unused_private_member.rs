@@ -0,0 +1,86 @@
+use crate::linter::lib::{VIRTUAL_METHODS, get_end_line_column, get_line_column, get_node_text};
+use crate::linter::rules::Rule;
+use crate::linter::scope::{ScopeTree, Symbol, SymbolKind};
+use crate::linter::{LintIssue, LintSeverity};
+use tree_sitter::Node;
+
+/// This rule checks for private class variables and functions, which start
+/// with an underscore, that are never used in the file that declares them.
+/// Exported variables, Godot's virtual methods, and signal callbacks named
+/// `_on_*`, which are often connected in the editor, are ignored.
+pub struct UnusedPrivateMemberRule;
+
+impl UnusedPrivateMemberRule {
+    /// Returns true if the variable has an `@export` annotation, either on its
+    /// line or on the lines above it.
+    fn is_exported(&self, node: &Node, source_code: &str) -> bool {
+        if node.kind() == "export_variable_statement" {
+            return true;
+        }
+        let is_export =
+            |annotation: &Node| get_node_text(annotation, source_code).starts_with("@export");
+        if let Some(first_child) = node.child(0)
+            && first_child.kind() == "annotations"
+        {
+            let mut cursor = first_child.walk();
+            if first_child
+                .children(&mut cursor)
+                .any(|annotation| is_export(&annotation))
+            {
+                return true;
+            }
+        }
+
+        let mut sibling = node.prev_named_sibling();
+        while let Some(annotation) = sibling.filter(|sibling| sibling.kind() == "annotation") {
+            if is_export(&annotation) {
+                return true;
+            }
+            sibling = annotation.prev_named_sibling();
+        }
+        false
+    }
+
+    fn is_ignored(&self, symbol: &Symbol, source_code: &str) -> bool {
+        match symbol.kind {
+            SymbolKind::Variable => self.is_exported(&symbol.declaration, source_code),
+            SymbolKind::Function => {
+                VIRTUAL_METHODS.contains(&symbol.name.as_str()) || symbol.name.starts_with("_on_")
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Rule for UnusedPrivateMemberRule {
+    fn check_scopes(&mut self, scopes: &ScopeTree, source_code: &str) -> Vec<LintIssue> {
+        let mut issues = Vec::new();
+
+        for (id, symbol) in scopes.symbols().iter().enumerate() {
+            if !symbol.name.starts_with('_')
+                || self.is_ignored(symbol, source_code)
+                || scopes.is_used(id)
+            {
+                continue;
+            }
+            let kind = match symbol.kind {
+                SymbolKind::Variable => "variable",
+                _ => "function",
+            };
+            let (line, column) = get_line_column(&symbol.name_node);
+            let (end_line, end_column) = get_end_line_column(&symbol.name_node);
+            issues.push(
+                LintIssue::new(
+                    line,
+                    column,
+                    "unused-private-member".to_string(),
+                    LintSeverity::Warning,
+                    format!("Private {} '{}' is never used", kind, symbol.name),
+                )
+                .with_end(end_line, end_column),
+            );
+        }
+
+        issues
+    }
+}
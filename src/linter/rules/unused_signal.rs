@@ -0,0 +1,31 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::rules::Rule;
+use crate::linter::scope::{ScopeTree, SymbolKind};
+use crate::linter::{LintIssue, LintSeverity};
+
+/// This rule checks for signals that are never emitted, connected, or
+/// otherwise referenced in the file that declares them.
+pub struct UnusedSignalRule;
+
+impl Rule for UnusedSignalRule {
+    fn check_scopes(&mut self, scopes: &ScopeTree, _source_code: &str) -> Vec<LintIssue> {
+        scopes
+            .symbols()
+            .iter()
+            .enumerate()
+            .filter(|(id, symbol)| symbol.kind == SymbolKind::Signal && !scopes.is_used(*id))
+            .map(|(_, symbol)| {
+                let (line, column) = get_line_column(&symbol.name_node);
+                let (end_line, end_column) = get_end_line_column(&symbol.name_node);
+                LintIssue::new(
+                    line,
+                    column,
+                    "unused-signal".to_string(),
+                    LintSeverity::Warning,
+                    format!("Signal '{}' is never used", symbol.name),
+                )
+                .with_end(end_line, end_column)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,10 @@
+use crate::linter::rules::Rule;
+
+/// Reports suppression comments that don't suppress any issue or that name
+/// rules that don't exist. The linter reports these issues itself after
+/// running the other rules, as it needs to know which comments suppressed
+/// issues, so this rule only makes the name available to configuration files
+/// and the `--disable` option.
+pub struct UnusedSuppressionRule;
+
+impl Rule for UnusedSuppressionRule {}
@@ -0,0 +1,38 @@
+use crate::linter::lib::{get_end_line_column, get_line_column};
+use crate::linter::rules::Rule;
+use crate::linter::scope::{ScopeTree, SymbolKind};
+use crate::linter::{LintIssue, LintSeverity};
+
+/// This rule checks for local variables that are declared but never used in
+/// their scope. Variables that start with an underscore are ignored.
+pub struct UnusedVariableRule;
+
+impl Rule for UnusedVariableRule {
+    fn check_scopes(&mut self, scopes: &ScopeTree, _source_code: &str) -> Vec<LintIssue> {
+        scopes
+            .symbols()
+            .iter()
+            .enumerate()
+            .filter(|(id, symbol)| {
+                symbol.kind == SymbolKind::LocalVariable
+                    && !symbol.name.starts_with('_')
+                    && !scopes.is_used(*id)
+            })
+            .map(|(_, symbol)| {
+                let (line, column) = get_line_column(&symbol.name_node);
+                let (end_line, end_column) = get_end_line_column(&symbol.name_node);
+                LintIssue::new(
+                    line,
+                    column,
+                    "unused-variable".to_string(),
+                    LintSeverity::Warning,
+                    format!(
+                        "Local variable '{}' is never used. Consider removing it or prefixing with '_'",
+                        symbol.name
+                    ),
+                )
+                .with_end(end_line, end_column)
+            })
+            .collect()
+    }
+}
@@ -1,17 +1,30 @@
-use crate::linter::lib::{get_line_column, get_node_text};
-use crate::linter::regex_patterns::{PASCAL_CASE, PRIVATE_SNAKE_CASE, SNAKE_CASE};
+use crate::linter::lib::{get_end_line_column, get_line_column, get_node_text};
+use crate::linter::regex_patterns::{NamingPattern, PASCAL_CASE, PRIVATE_SNAKE_CASE, SNAKE_CASE};
+use crate::linter::rule_config::RuleSettings;
 use crate::linter::rules::Rule;
 use crate::linter::{LintIssue, LintSeverity};
 use tree_sitter::Node;
-pub struct VariableNameRule;
+pub struct VariableNameRule {
+    pattern: NamingPattern,
+}
 
 impl VariableNameRule {
+    pub fn new(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            pattern: NamingPattern::from_settings(settings)?,
+        })
+    }
+
     fn is_valid_variable_name(&self, name: &str) -> bool {
-        SNAKE_CASE.is_match(name) || PRIVATE_SNAKE_CASE.is_match(name)
+        self.pattern.is_match(name, |name| {
+            SNAKE_CASE.is_match(name) || PRIVATE_SNAKE_CASE.is_match(name)
+        })
     }
 
+    /// Variables holding loaded resources can also use PascalCase, like
+    /// class names.
     fn is_valid_load_variable_name(&self, name: &str) -> bool {
-        PASCAL_CASE.is_match(name) || SNAKE_CASE.is_match(name) || PRIVATE_SNAKE_CASE.is_match(name)
+        PASCAL_CASE.is_match(name) || self.is_valid_variable_name(name)
     }
 
     fn is_load_call(&self, node: &Node, source_code: &str) -> bool {
@@ -51,31 +64,44 @@ impl Rule for VariableNameRule {
                 // For load() variables, only check load rules if they fail normal load validation
                 if !self.is_valid_load_variable_name(name) {
                     let (line, column) = get_line_column(&name_node);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "load-variable-name".to_string(),
-                        LintSeverity::Error,
-                        format!(
-                            "Variable name '{}' should be in PascalCase, snake_case or _private_snake_case format",
-                            name
-                        ),
-                    ));
+                    let (end_line, end_column) = get_end_line_column(&name_node);
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "load-variable-name".to_string(),
+                            LintSeverity::Error,
+                            format!(
+                                "Variable name '{}' should {}",
+                                name,
+                                self.pattern.describe_with_pascal_case(
+                                    "be in PascalCase, snake_case or _private_snake_case format"
+                                )
+                            ),
+                        )
+                        .with_end(end_line, end_column),
+                    );
                 }
             } else {
                 // For regular variables, just check regular rules
                 if !self.is_valid_variable_name(name) {
                     let (line, column) = get_line_column(&name_node);
-                    issues.push(LintIssue::new(
-                        line,
-                        column,
-                        "variable-name".to_string(),
-                        LintSeverity::Error,
-                        format!(
-                            "Variable name '{}' should be in snake_case or _private_snake_case format",
-                            name
-                        ),
-                    ));
+                    let (end_line, end_column) = get_end_line_column(&name_node);
+                    issues.push(
+                        LintIssue::new(
+                            line,
+                            column,
+                            "variable-name".to_string(),
+                            LintSeverity::Error,
+                            format!(
+                                "Variable name '{}' should {}",
+                                name,
+                                self.pattern
+                                    .describe("be in snake_case or _private_snake_case format")
+                            ),
+                        )
+                        .with_end(end_line, end_column),
+                    );
                 }
             }
         }
@@ -0,0 +1,606 @@
+//! Scope analysis: finds the declarations of a file and resolves each name to
+//! the declaration it refers to, following GDScript's scoping rules.
+//!
+//! Classes declare their members before any code runs, so functions can use
+//! members declared below them. In functions, parameters, local variables,
+//! loop variables, and match bindings are visible from their declaration to
+//! the end of their block, and lambdas can use the names of the functions
+//! containing them. Inner classes only see the constants, enums, and classes
+//! of the classes containing them.
+//!
+//! The linter builds the scopes of each file once and passes them to the rules
+//! that need to know which declaration a name refers to, so that a local
+//! `speed` and `obj.speed` or a lambda parameter named `speed` don't get mixed
+//! up.
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::Node;
+
+use super::lib::get_node_text;
+
+pub type ScopeId = usize;
+pub type SymbolId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The file or an inner class.
+    Class,
+    /// A function or constructor, with its parameters.
+    Function,
+    /// A lambda, with its parameters.
+    Lambda,
+    /// An indented block, a for loop, or a match pattern section.
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Constant,
+    Function,
+    Signal,
+    Enum,
+    Class,
+    Parameter,
+    LocalVariable,
+    LocalConstant,
+    LoopVariable,
+    MatchBinding,
+}
+
+impl SymbolKind {
+    /// Returns true for the symbols declared in functions and lambdas.
+    pub fn is_local(&self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Parameter
+                | SymbolKind::LocalVariable
+                | SymbolKind::LocalConstant
+                | SymbolKind::LoopVariable
+                | SymbolKind::MatchBinding
+        )
+    }
+
+    /// Returns the kind of symbol as it appears in messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SymbolKind::Variable => "class variable",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Function => "function",
+            SymbolKind::Signal => "signal",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Class => "class",
+            SymbolKind::Parameter => "function argument",
+            SymbolKind::LocalVariable => "local variable",
+            SymbolKind::LocalConstant => "local constant",
+            SymbolKind::LoopVariable => "loop variable",
+            SymbolKind::MatchBinding => "match binding",
+        }
+    }
+
+    /// Returns true for the class members that inner classes can use.
+    fn is_visible_from_inner_classes(&self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Constant | SymbolKind::Enum | SymbolKind::Class
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Scope<'tree> {
+    pub kind: ScopeKind,
+    /// The node that creates the scope.
+    pub node: Node<'tree>,
+    pub parent: Option<ScopeId>,
+    /// Local symbols of the functions containing a lambda that the lambda
+    /// uses. Empty for other scopes.
+    pub captures: Vec<SymbolId>,
+    symbols: HashMap<String, SymbolId>,
+}
+
+#[derive(Debug)]
+pub struct Symbol<'tree> {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub name_node: Node<'tree>,
+    /// The statement or parameter that declares the symbol.
+    pub declaration: Node<'tree>,
+    pub scope: ScopeId,
+    /// The identifiers that refer to the symbol.
+    pub references: Vec<Node<'tree>>,
+}
+
+/// The scopes and symbols of a file.
+pub struct ScopeTree<'tree> {
+    scopes: Vec<Scope<'tree>>,
+    symbols: Vec<Symbol<'tree>>,
+    /// Names that don't refer to any declaration in the file, like global
+    /// classes, built-in functions, and inherited members.
+    unresolved_names: HashSet<String>,
+    /// Names accessed on objects, like `speed` in `obj.speed`.
+    attribute_names: HashSet<String>,
+    /// Contents of string literals, which can name methods and signals in
+    /// calls like `call_deferred("_update")` or `emit_signal("died")`.
+    strings: HashSet<String>,
+}
+
+impl<'tree> ScopeTree<'tree> {
+    /// Builds the scopes of a file from the root node of its syntax tree.
+    pub fn new(root_node: Node<'tree>, source_code: &str) -> Self {
+        let mut builder = ScopeBuilder {
+            source_code,
+            tree: ScopeTree {
+                scopes: Vec::new(),
+                symbols: Vec::new(),
+                unresolved_names: HashSet::new(),
+                attribute_names: HashSet::new(),
+                strings: HashSet::new(),
+            },
+        };
+        let scope = builder.add_scope(ScopeKind::Class, root_node, None);
+        builder.visit_class_body(root_node, scope);
+        builder.tree
+    }
+
+    pub fn scopes(&self) -> &[Scope<'tree>] {
+        &self.scopes
+    }
+
+    pub fn symbols(&self) -> &[Symbol<'tree>] {
+        &self.symbols
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope<'tree> {
+        &self.scopes[id]
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol<'tree> {
+        &self.symbols[id]
+    }
+
+    /// Returns the symbol that `name` refers to in a scope, if it's declared
+    /// in the file.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        self.lookup_before(scope, name, usize::MAX)
+    }
+
+    /// Like `lookup()`, but ignores the local symbols declared after a byte
+    /// offset, which aren't visible there yet.
+    pub fn lookup_before(
+        &self,
+        scope: ScopeId,
+        name: &str,
+        byte_offset: usize,
+    ) -> Option<SymbolId> {
+        let mut current = Some(scope);
+        let mut is_in_inner_class = false;
+        while let Some(id) = current {
+            let scope = &self.scopes[id];
+            if let Some(&symbol) = scope.symbols.get(name) {
+                let symbol_kind = self.symbols[symbol].kind;
+                let is_visible = if symbol_kind.is_local() {
+                    self.symbols[symbol].name_node.start_byte() < byte_offset
+                } else {
+                    !is_in_inner_class || symbol_kind.is_visible_from_inner_classes()
+                };
+                if is_visible {
+                    return Some(symbol);
+                }
+            }
+            if scope.kind == ScopeKind::Class {
+                is_in_inner_class = true;
+            }
+            current = scope.parent;
+        }
+        None
+    }
+
+    /// Returns the symbol of an enclosing scope that a local symbol hides,
+    /// like a class member or a local variable of the function containing a
+    /// lambda.
+    pub fn find_shadowed_symbol(&self, id: SymbolId) -> Option<SymbolId> {
+        let symbol = &self.symbols[id];
+        let parent = self.scopes[symbol.scope].parent?;
+        self.lookup_before(parent, &symbol.name, symbol.name_node.start_byte())
+    }
+
+    /// Returns the innermost class containing a scope.
+    pub fn get_class_scope(&self, scope: ScopeId) -> ScopeId {
+        let mut current = scope;
+        while self.scopes[current].kind != ScopeKind::Class
+            && let Some(parent) = self.scopes[current].parent
+        {
+            current = parent;
+        }
+        current
+    }
+
+    /// Returns true if the symbol is used in the file. Other classes can use
+    /// class members through attributes, inheritance, or by name in strings,
+    /// so we consider members used if their name appears in any of these.
+    pub fn is_used(&self, id: SymbolId) -> bool {
+        let symbol = &self.symbols[id];
+        if !symbol.references.is_empty() {
+            return true;
+        }
+        !symbol.kind.is_local()
+            && (self.attribute_names.contains(&symbol.name)
+                || self.unresolved_names.contains(&symbol.name)
+                || self.strings.contains(&symbol.name))
+    }
+}
+
+struct ScopeBuilder<'tree, 'source> {
+    source_code: &'source str,
+    tree: ScopeTree<'tree>,
+}
+
+impl<'tree> ScopeBuilder<'tree, '_> {
+    fn add_scope(
+        &mut self,
+        kind: ScopeKind,
+        node: Node<'tree>,
+        parent: Option<ScopeId>,
+    ) -> ScopeId {
+        self.tree.scopes.push(Scope {
+            kind,
+            node,
+            parent,
+            captures: Vec::new(),
+            symbols: HashMap::new(),
+        });
+        self.tree.scopes.len() - 1
+    }
+
+    fn declare(
+        &mut self,
+        kind: SymbolKind,
+        name_node: Node<'tree>,
+        declaration: Node<'tree>,
+        scope: ScopeId,
+    ) {
+        let name = get_node_text(&name_node, self.source_code).to_string();
+        self.tree.symbols.push(Symbol {
+            name: name.clone(),
+            kind,
+            name_node,
+            declaration,
+            scope,
+            references: Vec::new(),
+        });
+        let id = self.tree.symbols.len() - 1;
+        self.tree.scopes[scope].symbols.insert(name, id);
+    }
+
+    /// Declares all the members of a class, then visits its statements.
+    fn visit_class_body(&mut self, body_node: Node<'tree>, scope: ScopeId) {
+        let mut cursor = body_node.walk();
+        for statement in body_node.named_children(&mut cursor) {
+            let kind = match statement.kind() {
+                "variable_statement"
+                | "export_variable_statement"
+                | "onready_variable_statement" => SymbolKind::Variable,
+                "const_statement" => SymbolKind::Constant,
+                "function_definition" => SymbolKind::Function,
+                "signal_statement" => SymbolKind::Signal,
+                "enum_definition" => SymbolKind::Enum,
+                "class_definition" => SymbolKind::Class,
+                _ => continue,
+            };
+            if let Some(name_node) = statement.child_by_field_name("name") {
+                self.declare(kind, name_node, statement, scope);
+            }
+        }
+        self.visit_children(body_node, scope);
+    }
+
+    fn visit(&mut self, node: Node<'tree>, scope: ScopeId) {
+        match node.kind() {
+            "variable_statement" | "const_statement"
+                if self.tree.scopes[scope].kind != ScopeKind::Class =>
+            {
+                // The value is resolved first: in `var speed = speed`, the
+                // value refers to the outer name
+                if let Some(value_node) = node.child_by_field_name("value") {
+                    self.visit(value_node, scope);
+                }
+                if let Some(name_node) = node.child_by_field_name("name") {
+                    let kind = if node.kind() == "variable_statement" {
+                        SymbolKind::LocalVariable
+                    } else {
+                        SymbolKind::LocalConstant
+                    };
+                    self.declare(kind, name_node, node, scope);
+                }
+            }
+            "function_definition" | "constructor_definition" => {
+                self.visit_function(node, ScopeKind::Function, scope)
+            }
+            "lambda" => self.visit_function(node, ScopeKind::Lambda, scope),
+            "class_definition" => {
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "class_body" {
+                        let class_scope = self.add_scope(ScopeKind::Class, node, Some(scope));
+                        self.visit_class_body(child, class_scope);
+                    } else {
+                        self.visit(child, scope);
+                    }
+                }
+            }
+            "body" => {
+                let block_scope = self.add_scope(ScopeKind::Block, node, Some(scope));
+                self.visit_children(node, block_scope);
+            }
+            "for_statement" => {
+                if let Some(iterable_node) = node.child_by_field_name("right") {
+                    self.visit(iterable_node, scope);
+                }
+                let loop_scope = self.add_scope(ScopeKind::Block, node, Some(scope));
+                if let Some(left_node) = node.child_by_field_name("left") {
+                    self.declare_parameter(SymbolKind::LoopVariable, left_node, loop_scope);
+                }
+                if let Some(body_node) = node.child_by_field_name("body") {
+                    self.visit_children(body_node, loop_scope);
+                }
+            }
+            "pattern_section" => {
+                // Names bound in the patterns are visible in the guard and
+                // the body of the section
+                let section_scope = self.add_scope(ScopeKind::Block, node, Some(scope));
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "body" {
+                        self.visit_children(child, section_scope);
+                    } else {
+                        self.visit(child, section_scope);
+                    }
+                }
+            }
+            "pattern_binding" => {
+                if let Some(name_node) = node.named_child(0) {
+                    self.declare(SymbolKind::MatchBinding, name_node, node, scope);
+                }
+            }
+            "identifier" => self.resolve(node, scope),
+            "string" | "string_name" => {
+                let text = get_node_text(&node, self.source_code);
+                let content = text.trim_start_matches('&').trim_matches(['"', '\'']);
+                self.tree.strings.insert(content.to_string());
+            }
+            // Type hints only contain class names
+            "type" => {}
+            _ => self.visit_children(node, scope),
+        }
+    }
+
+    fn visit_children(&mut self, node: Node<'tree>, scope: ScopeId) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, scope);
+        }
+    }
+
+    /// Visits a function, constructor, or lambda. The parameters and the
+    /// statements of the body share the same scope.
+    fn visit_function(&mut self, node: Node<'tree>, kind: ScopeKind, parent: ScopeId) {
+        let scope = self.add_scope(kind, node, Some(parent));
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "parameters" => {
+                    let mut parameters_cursor = child.walk();
+                    for parameter_node in child.named_children(&mut parameters_cursor) {
+                        // Default values are resolved before the parameter is
+                        // declared
+                        let mut parameter_cursor = parameter_node.walk();
+                        for value_node in
+                            parameter_node.named_children(&mut parameter_cursor).skip(1)
+                        {
+                            self.visit(value_node, scope);
+                        }
+                        self.declare_parameter(SymbolKind::Parameter, parameter_node, scope);
+                    }
+                }
+                "body" => self.visit_children(child, scope),
+                // The name of the function is declared in the class
+                "name" => {}
+                _ => self.visit(child, scope),
+            }
+        }
+    }
+
+    /// Declares a parameter or loop variable, which can have a type and a
+    /// default value after the name.
+    fn declare_parameter(&mut self, kind: SymbolKind, node: Node<'tree>, scope: ScopeId) {
+        let name_node = if node.kind() == "identifier" {
+            Some(node)
+        } else {
+            node.child(0)
+        };
+        if let Some(name_node) = name_node {
+            self.declare(kind, name_node, node, scope);
+        }
+    }
+
+    fn resolve(&mut self, node: Node<'tree>, scope: ScopeId) {
+        let name = get_node_text(&node, self.source_code);
+        if is_attribute_name(&node) {
+            self.tree.attribute_names.insert(name.to_string());
+            return;
+        }
+
+        let Some(symbol) = self.tree.lookup(scope, name) else {
+            self.tree.unresolved_names.insert(name.to_string());
+            return;
+        };
+        self.tree.symbols[symbol].references.push(node);
+
+        // Lambdas between the reference and the declaration of a local
+        // symbol capture it
+        if self.tree.symbols[symbol].kind.is_local() {
+            let declaration_scope = self.tree.symbols[symbol].scope;
+            let mut current = Some(scope);
+            while let Some(id) = current
+                && id != declaration_scope
+            {
+                let scope = &mut self.tree.scopes[id];
+                if scope.kind == ScopeKind::Lambda && !scope.captures.contains(&symbol) {
+                    scope.captures.push(symbol);
+                }
+                current = scope.parent;
+            }
+        }
+    }
+}
+
+/// Returns true if the identifier is the name of an attribute or method
+/// accessed on an object, like `speed` in `obj.speed` or `move` in
+/// `obj.move()`.
+fn is_attribute_name(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_first_child = parent.child(0).is_some_and(|child| child.id() == node.id());
+    match parent.kind() {
+        "attribute" => !is_first_child,
+        "attribute_call" | "attribute_subscript" => is_first_child,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::{Parser, Tree};
+
+    fn parse(source: &str) -> Tree {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+            .unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    fn find_symbols<'a, 'tree>(scopes: &'a ScopeTree<'tree>, name: &str) -> Vec<&'a Symbol<'tree>> {
+        scopes
+            .symbols()
+            .iter()
+            .filter(|symbol| symbol.name == name)
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_shadowed_names() {
+        let source = r#"var speed = 10.0
+
+func move(delta):
+	var speed = 2.0
+	var callback = func(speed): return speed * delta
+	callback.call(self.speed)
+"#;
+        let tree = parse(source);
+        let scopes = ScopeTree::new(tree.root_node(), source);
+
+        let speeds = find_symbols(&scopes, "speed");
+        assert_eq!(speeds.len(), 3);
+        assert_eq!(speeds[0].kind, SymbolKind::Variable);
+        assert_eq!(speeds[1].kind, SymbolKind::LocalVariable);
+        assert_eq!(speeds[2].kind, SymbolKind::Parameter);
+        // Only the lambda parameter is used by name, the class variable is
+        // used as an attribute
+        assert!(speeds[0].references.is_empty());
+        assert!(speeds[1].references.is_empty());
+        assert_eq!(speeds[2].references.len(), 1);
+        assert!(scopes.is_used(0));
+
+        // The lambda captures the delta parameter of the function
+        let lambda_scope = scopes
+            .scopes()
+            .iter()
+            .find(|scope| scope.kind == ScopeKind::Lambda)
+            .unwrap();
+        let captures: Vec<&str> = lambda_scope
+            .captures
+            .iter()
+            .map(|&id| scopes.symbol(id).name.as_str())
+            .collect();
+        assert_eq!(captures, vec!["delta"]);
+    }
+
+    #[test]
+    fn test_resolve_blocks_and_inner_classes() {
+        let source = r#"const MAX_HEALTH = 100
+var health = 0
+
+
+func heal(amounts):
+	for amount in amounts:
+		health += amount
+	match health:
+		[var first]:
+			print(first)
+	if health > MAX_HEALTH:
+		var excess = health - MAX_HEALTH
+	print(amount)
+
+
+class Inner:
+	func get_health():
+		return health + MAX_HEALTH
+"#;
+        let tree = parse(source);
+        let scopes = ScopeTree::new(tree.root_node(), source);
+
+        let amount = find_symbols(&scopes, "amount")[0];
+        assert_eq!(amount.kind, SymbolKind::LoopVariable);
+        // The last print is outside of the loop
+        assert_eq!(amount.references.len(), 1);
+
+        let first = find_symbols(&scopes, "first")[0];
+        assert_eq!(first.kind, SymbolKind::MatchBinding);
+        assert_eq!(first.references.len(), 1);
+
+        let excess = find_symbols(&scopes, "excess")[0];
+        assert_eq!(excess.kind, SymbolKind::LocalVariable);
+        assert!(excess.references.is_empty());
+
+        // Inner classes can use the constants of the outer class, but not its
+        // variables
+        let health = find_symbols(&scopes, "health")[0];
+        assert_eq!(health.references.len(), 4);
+        let max_health = find_symbols(&scopes, "MAX_HEALTH")[0];
+        assert_eq!(max_health.references.len(), 3);
+    }
+
+    #[test]
+    fn test_find_shadowed_symbol() {
+        let source = r#"var speed = 10.0
+
+
+func move():
+	if speed > 0:
+		var speed = 1.0
+		var distance = 2.0
+	var distance = 3.0
+"#;
+        let tree = parse(source);
+        let scopes = ScopeTree::new(tree.root_node(), source);
+
+        let local_speed = scopes
+            .symbols()
+            .iter()
+            .position(|symbol| symbol.kind == SymbolKind::LocalVariable)
+            .unwrap();
+        assert_eq!(scopes.find_shadowed_symbol(local_speed), Some(0));
+
+        // The second distance is declared after the block, so the first one
+        // doesn't shadow it
+        let distances: Vec<SymbolId> = (0..scopes.symbols().len())
+            .filter(|&id| scopes.symbol(id).name == "distance")
+            .collect();
+        assert_eq!(distances.len(), 2);
+        assert_eq!(scopes.find_shadowed_symbol(distances[0]), None);
+    }
+}
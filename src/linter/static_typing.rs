@@ -0,0 +1,67 @@
+//! Helpers shared by the rules that enforce static typing, like
+//! `untyped-declaration` and `missing-return-type`.
+//!
+//! These rules are opt-in, and each of them accepts two options to relax them:
+//! `ignore_private` skips the code of private helper functions, whose name
+//! starts with an underscore, and `ignore_lambdas` skips the code of lambdas.
+use tree_sitter::Node;
+
+use super::lib::{VIRTUAL_METHODS, get_node_text};
+use super::rule_config::RuleSettings;
+
+pub const IGNORE_PRIVATE_OPTION: &str = "ignore_private";
+pub const IGNORE_LAMBDAS_OPTION: &str = "ignore_lambdas";
+
+/// Options of all the static typing rules.
+pub const STATIC_TYPING_OPTIONS: &[&str] = &[IGNORE_PRIVATE_OPTION, IGNORE_LAMBDAS_OPTION];
+
+/// The parts of the code that a static typing rule doesn't check, set with
+/// the rule's options.
+pub struct TypingExemptions {
+    ignore_private: bool,
+    ignore_lambdas: bool,
+}
+
+impl TypingExemptions {
+    pub fn from_settings(settings: &RuleSettings) -> Result<Self, String> {
+        Ok(Self {
+            ignore_private: settings.get_bool(IGNORE_PRIVATE_OPTION)?.unwrap_or(false),
+            ignore_lambdas: settings.get_bool(IGNORE_LAMBDAS_OPTION)?.unwrap_or(false),
+        })
+    }
+
+    /// Returns true if `node` is, or is inside of, a function the rule
+    /// shouldn't check.
+    pub fn is_exempt(&self, node: &Node, source_code: &str) -> bool {
+        let mut current = Some(*node);
+        while let Some(ancestor) = current {
+            match ancestor.kind() {
+                "lambda" if self.ignore_lambdas => return true,
+                "function_definition"
+                    if self.ignore_private && is_private_helper(&ancestor, source_code) =>
+                {
+                    return true;
+                }
+                _ => {}
+            }
+            current = ancestor.parent();
+        }
+        false
+    }
+}
+
+/// Returns true for functions that start with an underscore, except for the
+/// virtual methods that Godot calls.
+fn is_private_helper(function_node: &Node, source_code: &str) -> bool {
+    function_node
+        .child_by_field_name("name")
+        .map(|name_node| get_node_text(&name_node, source_code))
+        .is_some_and(|name| name.starts_with('_') && !VIRTUAL_METHODS.contains(&name))
+}
+
+/// Returns true if the declaration infers its type from its value with `:=`.
+pub fn has_inferred_type(node: &Node) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| matches!(child.kind(), ":=" | "inferred_type"))
+}
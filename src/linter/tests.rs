@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod tests {
+    use crate::error::LintError;
     use crate::linter::{LintIssue, LintSeverity, LinterConfig, lint_gdscript_with_config};
 
     #[test]
@@ -96,4 +97,259 @@ signal BadSignal
 
         assert!(crate::linter::rule_config::validate_rule_names(&invalid_rules).is_err());
     }
+
+    #[test]
+    fn test_lint_fix() {
+        let test_code = r#"func get_speed(delta, is_running):
+	pass
+	if is_running:
+		return 10.0
+	else:
+		var speed = 5.0
+		return speed
+"#;
+        let expected_code = r#"func get_speed(_delta, is_running):
+	if is_running:
+		return 10.0
+	var speed = 5.0
+	return speed
+"#;
+
+        let mut linter = crate::linter::GDScriptLinter::new(LinterConfig::default()).unwrap();
+        let fixed_code = linter.fix(test_code, "test.gd", false).unwrap();
+        assert_eq!(fixed_code, expected_code);
+    }
+
+    #[test]
+    fn test_lint_unsafe_fix() {
+        let test_code = "func is_valid(value):\n\treturn value == value\n";
+
+        let mut linter = crate::linter::GDScriptLinter::new(LinterConfig::default()).unwrap();
+        assert_eq!(linter.fix(test_code, "test.gd", false).unwrap(), test_code);
+        assert_eq!(
+            linter.fix(test_code, "test.gd", true).unwrap(),
+            "func is_valid(value):\n\treturn true\n"
+        );
+    }
+
+    #[test]
+    fn test_lint_rule_settings() {
+        let test_code = r#"
+class_name badClassName
+func getSpeed():
+    pass
+"#;
+
+        let mut config = LinterConfig::default();
+        config.rules.insert(
+            "class-name".to_string(),
+            toml::from_str("severity = \"info\"").unwrap(),
+        );
+        config.rules.insert(
+            "function-name".to_string(),
+            toml::from_str("pattern = \"^[a-z][a-zA-Z0-9]*$\"").unwrap(),
+        );
+        config.rules.insert(
+            "unnecessary-pass".to_string(),
+            toml::from_str("severity = \"off\"").unwrap(),
+        );
+
+        let issues = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule, "class-name");
+        assert_eq!(issues[0].severity, LintSeverity::Info);
+
+        config.rules.insert(
+            "function-name".to_string(),
+            toml::from_str("pattern = \"^get_\"").unwrap(),
+        );
+        let issues = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap();
+        let function_issue = issues
+            .iter()
+            .find(|issue| issue.rule == "function-name")
+            .unwrap();
+        assert_eq!(
+            function_issue.message,
+            "Function name 'getSpeed' should match the pattern '^get_'"
+        );
+
+        config.rules.insert(
+            "function-name".to_string(),
+            toml::from_str("pattern = \"[\"").unwrap(),
+        );
+        let error = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap_err();
+        assert!(matches!(
+            error,
+            LintError::RuleSettings {
+                rule: "function-name",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_lint_rule_alias_severity() {
+        // Issues reported under an alias use the settings of their rule
+        let test_code = "var badScene = preload(\"res://scene.tscn\")\n";
+        let mut config = LinterConfig::default();
+        config.rules.insert(
+            "variable-name".to_string(),
+            toml::from_str("severity = \"info\"").unwrap(),
+        );
+        let issues = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap();
+        let issue = issues
+            .iter()
+            .find(|issue| issue.rule == "load-variable-name")
+            .unwrap();
+        assert_eq!(issue.severity, LintSeverity::Info);
+    }
+
+    #[test]
+    fn test_max_line_length_columns() {
+        // Tabs count as 4 characters, and each "é" takes 2 bytes
+        let test_code = "func _ready():\n\tprint(\"éé\")\n";
+        let config = LinterConfig {
+            max_line_length: 14,
+            ..Default::default()
+        };
+        let issues = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap();
+        let issue = issues
+            .iter()
+            .find(|issue| issue.rule == "max-line-length")
+            .unwrap();
+        assert_eq!(
+            (issue.line, issue.column, issue.end_line, issue.end_column),
+            (2, 14, 2, 15)
+        );
+    }
+
+    #[test]
+    fn test_lint_metric_thresholds() {
+        let test_code =
+            "func _ready():\n\tif true:\n\t\tprint(1)\n\n\nfunc _process(delta):\n\tprint(delta)\n";
+
+        let issues =
+            lint_gdscript_with_config(test_code, "test.gd", &LinterConfig::default()).unwrap();
+        assert!(issues.is_empty());
+
+        let config = LinterConfig {
+            max_file_length: 5,
+            max_function_length: 2,
+            max_nesting_depth: 0,
+            ..Default::default()
+        };
+        let issues = lint_gdscript_with_config(test_code, "test.gd", &config).unwrap();
+        let found: Vec<(usize, &str)> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.rule.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "max-function-length"),
+                (1, "max-nesting-depth"),
+                (6, "max-file-length"),
+            ]
+        );
+        assert_eq!(
+            issues[2].message,
+            "File is too long. Found 7 lines, maximum allowed is 5"
+        );
+    }
+
+    #[test]
+    fn test_lint_static_typing_rules() {
+        let test_code = r#"var speed = 10
+var health: int = 100
+var mana := 50
+
+
+func _ready():
+	var direction = Vector2.ZERO
+	for i in range(3):
+		print(i, direction)
+	for j: int in range(3):
+		print(j)
+
+
+func move(delta, factor := 2.0, scale: float = 1.0) -> void:
+	var callback = func(value): return value * delta
+	print(callback.call(factor * scale))
+
+
+func _helper(value):
+	var result = value
+	return result
+"#;
+        let typing_rules = [
+            "untyped-declaration",
+            "missing-return-type",
+            "untyped-parameter",
+            "untyped-loop-variable",
+        ];
+        let find_typing_issues = |config: &LinterConfig| {
+            lint_gdscript_with_config(test_code, "test.gd", config)
+                .unwrap()
+                .into_iter()
+                .filter(|issue| typing_rules.contains(&issue.rule.as_str()))
+                .map(|issue| (issue.line, issue.rule))
+                .collect::<Vec<_>>()
+        };
+
+        // The static typing rules are opt-in
+        let mut config = LinterConfig::default();
+        assert!(find_typing_issues(&config).is_empty());
+
+        for rule in typing_rules {
+            config.rules.insert(
+                rule.to_string(),
+                toml::from_str("severity = \"warning\"").unwrap(),
+            );
+        }
+        let expected = [
+            (1, "untyped-declaration"),
+            (6, "missing-return-type"),
+            (7, "untyped-declaration"),
+            (8, "untyped-loop-variable"),
+            (14, "untyped-parameter"),
+            (15, "untyped-declaration"),
+            (15, "missing-return-type"),
+            (15, "untyped-parameter"),
+            (19, "missing-return-type"),
+            (19, "untyped-parameter"),
+            (20, "untyped-declaration"),
+        ];
+        assert_eq!(
+            find_typing_issues(&config),
+            expected.map(|(line, rule)| (line, rule.to_string()))
+        );
+
+        // Setting any option turns an opt-in rule on, even without a severity
+        for rule in typing_rules {
+            config.rules.insert(
+                rule.to_string(),
+                toml::from_str("ignore_private = true\nignore_lambdas = true").unwrap(),
+            );
+        }
+        let expected = [
+            (1, "untyped-declaration"),
+            (6, "missing-return-type"),
+            (7, "untyped-declaration"),
+            (8, "untyped-loop-variable"),
+            (14, "untyped-parameter"),
+            (15, "untyped-declaration"),
+        ];
+        assert_eq!(
+            find_typing_issues(&config),
+            expected.map(|(line, rule)| (line, rule.to_string()))
+        );
+
+        for rule in typing_rules {
+            config.rules.insert(
+                rule.to_string(),
+                toml::from_str("severity = \"off\"\nignore_private = true").unwrap(),
+            );
+        }
+        assert!(find_typing_issues(&config).is_empty());
+    }
 }
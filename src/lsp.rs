@@ -0,0 +1,456 @@
+//! This module implements a language server that code editors can run to
+//! format GDScript files and show lint issues as diagnostics, without starting
+//! a new process on every save.
+//!
+//! The server communicates with the editor over standard input and output
+//! using the Language Server Protocol. It supports:
+//!
+//! - Formatting whole documents (`textDocument/formatting`)
+//! - Formatting a selection (`textDocument/rangeFormatting`)
+//! - Publishing lint issues when documents are opened, changed, or saved, with
+//!   the rule names as diagnostic codes
+//!
+//! Settings come from the `gdformat.toml` files that apply to each document,
+//! like on the command line.
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics, ShowMessage,
+};
+use lsp_types::request::{Formatting, RangeFormatting, Request as _};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentFormattingParams,
+    DocumentRangeFormattingParams, FormattingOptions, MessageType, NumberOrString, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, ShowMessageParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+
+use crate::FormatterConfig;
+use crate::config::{ConfigResolver, FormatOptions, LintOptions};
+use crate::formatter::{LineRange, format_gdscript_lines, format_gdscript_with_config};
+use crate::linter::{GDScriptLinter, LintIssue, LintSeverity};
+
+type ServerError = Box<dyn std::error::Error + Sync + Send>;
+
+/// Name shown by editors as the source of diagnostics.
+const DIAGNOSTIC_SOURCE: &str = "gdscript-formatter";
+
+/// A GDScript file opened in the editor.
+struct Document {
+    text: String,
+    /// Path of the file on disk, used to find configuration files. None for
+    /// unsaved documents.
+    path: Option<PathBuf>,
+    /// Each document keeps its own linter, and with it its own parser.
+    linter: GDScriptLinter,
+}
+
+struct Server {
+    connection: Connection,
+    documents: HashMap<Uri, Document>,
+}
+
+/// Runs the language server over standard input and output until the editor
+/// asks it to exit.
+pub fn run_server() -> Result<(), ServerError> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server {
+        connection,
+        documents: HashMap::new(),
+    };
+    server.run()?;
+
+    // The connection must be dropped before joining the threads, otherwise the
+    // writer thread never stops
+    drop(server);
+    io_threads.join()?;
+    Ok(())
+}
+
+impl Server {
+    fn run(&mut self) -> Result<(), ServerError> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                // Errors in notifications, like an invalid configuration file,
+                // must not stop the server, so we show them to the user instead
+                Message::Notification(notification) => {
+                    if let Err(error) = self.handle_notification(notification) {
+                        self.show_error(error.to_string())?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), ServerError> {
+        let result = match request.method.as_str() {
+            Formatting::METHOD => serde_json::from_value(request.params)
+                .map_err(|error| error.to_string())
+                .and_then(|params| self.format_document(params)),
+            RangeFormatting::METHOD => serde_json::from_value(request.params)
+                .map_err(|error| error.to_string())
+                .and_then(|params| self.format_document_range(params)),
+            _ => {
+                let response = Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request: {}", request.method),
+                );
+                self.connection.sender.send(response.into())?;
+                return Ok(());
+            }
+        };
+
+        let response = match result {
+            Ok(edits) => Response::new_ok(request.id, edits),
+            Err(message) => Response::new_err(request.id, ErrorCode::RequestFailed as i32, message),
+        };
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), ServerError> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                let document = Document {
+                    text: params.text_document.text,
+                    path: uri_to_path(&uri),
+                    linter: GDScriptLinter::new(Default::default())?,
+                };
+                self.documents.insert(uri.clone(), document);
+                self.publish_diagnostics(&uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                // We use full document synchronization, so the last change
+                // contains the whole text
+                if let Some(document) = self.documents.get_mut(&uri)
+                    && let Some(change) = params.content_changes.into_iter().last()
+                {
+                    document.text = change.text;
+                }
+                self.publish_diagnostics(&uri)?;
+            }
+            DidSaveTextDocument::METHOD => {
+                let params: DidSaveTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.publish_diagnostics(&params.text_document.uri)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.send_diagnostics(uri, Vec::new())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn format_document(&self, params: DocumentFormattingParams) -> Result<Vec<TextEdit>, String> {
+        let document = self.get_document(&params.text_document.uri)?;
+        let config = get_formatter_config(document, &params.options)?;
+        let formatted = format_gdscript_with_config(&document.text, &config)
+            .map_err(|error| format!("Failed to format the document: {}", error))?;
+        Ok(get_text_edits(&document.text, formatted))
+    }
+
+    fn format_document_range(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Vec<TextEdit>, String> {
+        let document = self.get_document(&params.text_document.uri)?;
+        let config = get_formatter_config(document, &params.options)?;
+
+        // LSP lines start at 0, and a range that ends at the start of a line
+        // doesn't include that line
+        let start = params.range.start.line as usize + 1;
+        let mut end = params.range.end.line as usize + 1;
+        if params.range.end.character == 0 && end > start {
+            end -= 1;
+        }
+        let formatted = format_gdscript_lines(&document.text, &config, &[LineRange { start, end }])
+            .map_err(|error| format!("Failed to format the selection: {}", error))?;
+        Ok(get_text_edits(&document.text, formatted))
+    }
+
+    fn get_document(&self, uri: &Uri) -> Result<&Document, String> {
+        self.documents
+            .get(uri)
+            .ok_or_else(|| format!("Unknown document: {}", uri.as_str()))
+    }
+
+    /// Lints the document and sends the issues to the editor as diagnostics.
+    fn publish_diagnostics(&mut self, uri: &Uri) -> Result<(), ServerError> {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return Ok(());
+        };
+
+        let config = match &document.path {
+            Some(path) => ConfigResolver::new().linter_config(path, &LintOptions::default())?,
+            None => Default::default(),
+        };
+        document.linter.set_config(config);
+        let issues = document.linter.lint(&document.text, uri.as_str())?;
+
+        let lines: Vec<&str> = document.text.lines().collect();
+        let diagnostics = issues
+            .into_iter()
+            .map(|issue| get_diagnostic(issue, &lines))
+            .collect();
+
+        self.send_diagnostics(uri.clone(), diagnostics)
+    }
+
+    fn show_error(&self, message: String) -> Result<(), ServerError> {
+        let params = ShowMessageParams {
+            typ: MessageType::ERROR,
+            message: format!("{}: {}", DIAGNOSTIC_SOURCE, message),
+        };
+        let notification = Notification::new(ShowMessage::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn send_diagnostics(&self, uri: Uri, diagnostics: Vec<Diagnostic>) -> Result<(), ServerError> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
+
+/// Converts a lint issue to a diagnostic. `lines` are the lines of the
+/// document, to convert columns to UTF-16.
+fn get_diagnostic(issue: LintIssue, lines: &[&str]) -> Diagnostic {
+    let start_row = issue.line.saturating_sub(1);
+    let start_line = lines.get(start_row).copied().unwrap_or_default();
+    let start_column = get_utf16_column(start_line, issue.column.saturating_sub(1));
+
+    // Issues that only have a start position highlight until the end of the
+    // line
+    let (end_row, end_column) = if (issue.end_line, issue.end_column) == (issue.line, issue.column)
+    {
+        let end_column = get_utf16_column(start_line, start_line.len()).max(start_column);
+        (start_row, end_column)
+    } else {
+        let end_row = issue.end_line.saturating_sub(1);
+        let end_line = lines.get(end_row).copied().unwrap_or_default();
+        (
+            end_row,
+            get_utf16_column(end_line, issue.end_column.saturating_sub(1)),
+        )
+    };
+
+    Diagnostic {
+        range: Range::new(
+            Position::new(start_row as u32, start_column),
+            Position::new(end_row as u32, end_column),
+        ),
+        severity: Some(match issue.severity {
+            LintSeverity::Error => DiagnosticSeverity::ERROR,
+            LintSeverity::Warning => DiagnosticSeverity::WARNING,
+            LintSeverity::Info => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(issue.rule)),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: issue.message,
+        ..Default::default()
+    }
+}
+
+/// Returns the formatter configuration for a document. Configuration files
+/// take precedence over the editor's indentation settings, so that everyone
+/// working on a project gets the same result.
+fn get_formatter_config(
+    document: &Document,
+    options: &FormattingOptions,
+) -> Result<FormatterConfig, String> {
+    let mut format_options = FormatOptions {
+        use_spaces: Some(options.insert_spaces),
+        indent_size: Some(options.tab_size as usize),
+        ..Default::default()
+    };
+    if let Some(path) = &document.path {
+        format_options.merge(&ConfigResolver::new().resolve(path)?.format);
+    }
+    Ok(format_options.to_formatter_config())
+}
+
+/// Returns an edit that replaces the whole document with the formatted code,
+/// or no edit if the code is already formatted.
+fn get_text_edits(text: &str, formatted: String) -> Vec<TextEdit> {
+    if text == formatted {
+        return Vec::new();
+    }
+
+    let last_row = text.split('\n').count() - 1;
+    let last_line = text.rsplit('\n').next().unwrap_or_default();
+    let end = Position::new(
+        last_row as u32,
+        get_utf16_column(last_line, last_line.len()),
+    );
+    vec![TextEdit::new(
+        Range::new(Position::new(0, 0), end),
+        formatted,
+    )]
+}
+
+/// Converts a byte column to the UTF-16 column that LSP uses by default.
+fn get_utf16_column(line: &str, byte_column: usize) -> u32 {
+    let mut byte_column = byte_column.min(line.len());
+    while !line.is_char_boundary(byte_column) {
+        byte_column -= 1;
+    }
+    line[..byte_column].encode_utf16().count() as u32
+}
+
+/// Returns the path of a `file://` URI.
+fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme()?.as_str() != "file" {
+        return None;
+    }
+    let path = uri.path().as_estr().decode().into_string().ok()?;
+    Some(PathBuf::from(path.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_text_edits() {
+        assert!(get_text_edits("var a = 1\n", "var a = 1\n".to_string()).is_empty());
+
+        let edits = get_text_edits("var é=1\nvar b=2", "var é = 1\nvar b = 2\n".to_string());
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position::new(0, 0));
+        assert_eq!(edits[0].range.end, Position::new(1, 7));
+        assert_eq!(get_utf16_column("var é=1", 7), 6);
+    }
+
+    #[test]
+    fn test_get_diagnostic() {
+        let lines = ["var é = some_value", "func _ready():"];
+        let issue = |line, column| {
+            LintIssue::new(
+                line,
+                column,
+                "rule".to_string(),
+                LintSeverity::Warning,
+                "message".to_string(),
+            )
+        };
+
+        // The range ends at the end position of the issue
+        let diagnostic = get_diagnostic(issue(1, 10).with_end(1, 14), &lines);
+        assert_eq!(diagnostic.range.start, Position::new(0, 8));
+        assert_eq!(diagnostic.range.end, Position::new(0, 12));
+
+        let diagnostic = get_diagnostic(issue(1, 1).with_end(2, 5), &lines);
+        assert_eq!(diagnostic.range.end, Position::new(1, 4));
+
+        // Without an end position, the range ends at the end of the line
+        let diagnostic = get_diagnostic(issue(1, 10), &lines);
+        assert_eq!(diagnostic.range.end, Position::new(0, 18));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[test]
+    fn test_invalid_config_does_not_stop_server() {
+        let directory = std::env::temp_dir().join(format!(
+            "gdscript_formatter_lsp_invalid_config_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join(crate::config::CONFIG_FILE_NAME),
+            "[lint]\ndisabled_rules = [\"not-a-rule\"]\n",
+        )
+        .unwrap();
+        let uri: Uri = format!("file://{}", directory.join("script.gd").display())
+            .parse()
+            .unwrap();
+
+        let (server_connection, client_connection) = Connection::memory();
+        let server_thread = std::thread::spawn(move || {
+            let mut server = Server {
+                connection: server_connection,
+                documents: HashMap::new(),
+            };
+            server.run().map_err(|error| error.to_string())
+        });
+
+        let open_params = DidOpenTextDocumentParams {
+            text_document: lsp_types::TextDocumentItem::new(
+                uri.clone(),
+                "gdscript".to_string(),
+                1,
+                "var a=1\n".to_string(),
+            ),
+        };
+        let notification = Notification::new(DidOpenTextDocument::METHOD.to_string(), open_params);
+        client_connection.sender.send(notification.into()).unwrap();
+
+        let format_params = DocumentFormattingParams {
+            text_document: lsp_types::TextDocumentIdentifier::new(uri),
+            options: FormattingOptions {
+                tab_size: 4,
+                insert_spaces: false,
+                ..Default::default()
+            },
+            work_done_progress_params: Default::default(),
+        };
+        let request = Request::new(1.into(), Formatting::METHOD.to_string(), format_params);
+        client_connection.sender.send(request.into()).unwrap();
+
+        let mut received_error_message = false;
+        let response = loop {
+            match client_connection.receiver.recv().unwrap() {
+                Message::Notification(notification)
+                    if notification.method == ShowMessage::METHOD =>
+                {
+                    received_error_message = true;
+                }
+                Message::Response(response) => break response,
+                _ => {}
+            }
+        };
+        assert!(received_error_message);
+        let edits: Vec<TextEdit> = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(edits[0].new_text, "var a = 1\n");
+
+        // Closing the connection stops the server
+        drop(client_connection);
+        assert!(server_thread.join().unwrap().is_ok());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -4,16 +4,25 @@ use std::{
     path::PathBuf,
 };
 
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, ValueEnum};
 use rayon::prelude::*;
 
+use gdscript_formatter::FormatterConfig;
+use gdscript_formatter::config::{ConfigResolver, FormatOptions, LintOptions};
+use gdscript_formatter::diff::{FileChanges, changed_line_ranges, unified_diff};
+use gdscript_formatter::error::FormatError;
+use gdscript_formatter::files::find_gdscript_files;
+use gdscript_formatter::formatter::{
+    LineRange, format_gdscript_lines, format_gdscript_with_config,
+};
+use gdscript_formatter::linter::baseline::BaselineMode;
+use gdscript_formatter::linter::output::OutputFormat;
 use gdscript_formatter::linter::rule_config::{
     get_all_rule_names, parse_disabled_rules, validate_rule_names,
 };
-use gdscript_formatter::{
-    FormatterConfig, formatter::format_gdscript_with_config, linter::LinterConfig,
-};
-use std::collections::HashSet;
+use gdscript_formatter::linter::rules::OPT_IN_RULES;
+use gdscript_formatter::linter::{FixOptions, LintSeverity};
+use gdscript_formatter::migrate::migrate_gdscript;
 
 /// This struct is used to hold all the information about the result when
 /// formatting a single file. Now that we use parallel processing, we need to
@@ -23,6 +32,7 @@ use std::collections::HashSet;
 struct FormatterOutput {
     index: usize,
     file_path: PathBuf,
+    input_content: String,
     formatted_content: String,
     is_formatted: bool,
 }
@@ -36,6 +46,11 @@ struct FormatterOutput {
 /// By default, the formatter overwrites input files with the formatted code.
 /// Use the --stdout flag to output to standard output instead.
 ///
+/// Settings can also be stored in gdformat.toml files. The formatter looks for
+/// them in the folder of each input file and its parent folders, up to the
+/// folder containing project.godot. Command line options take precedence over
+/// configuration files.
+///
 /// The latest version of the GDScript style guide can be found at:
 /// https://docs.godotengine.org/en/stable/tutorials/scripting/gdscript/gdscript_styleguide.html
 #[clap(
@@ -44,9 +59,14 @@ struct FormatterOutput {
     max_term_width = 120
 )]
 struct Args {
-    /// The GDScript file(s) to format. If no file paths are provided, the
-    /// program reads from standard input and outputs to standard output.
-    #[arg(value_name = "FILES")]
+    /// The GDScript file(s) or directories to format. If no file paths are
+    /// provided, the program reads from standard input and outputs to standard
+    /// output.
+    ///
+    /// Directories are searched recursively for .gd files, skipping the .godot
+    /// folder, folders containing a .gdignore file, and paths matched by
+    /// .gitignore files.
+    #[arg(value_name = "PATHS")]
     input: Vec<PathBuf>,
 
     #[command(subcommand)]
@@ -69,17 +89,56 @@ struct Args {
     #[arg(short, long)]
     check: bool,
 
+    /// Show the changes the formatter would make to FILES, making no changes.
+    ///
+    /// Like --check, exits with code 1 if any file is not formatted. Use
+    /// --diff-format to choose between a unified diff and a JSON list of
+    /// changed line ranges.
+    #[arg(long, conflicts_with = "stdout")]
+    diff: bool,
+
+    /// Output format of --diff.
+    #[arg(long, value_enum, default_value_t = DiffFormat::Unified, requires = "diff")]
+    diff_format: DiffFormat,
+
+    /// When to color the output of --diff. "auto" colors the output only when
+    /// writing to a terminal.
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
     /// Use spaces for indentation instead of tabs.
     ///
     /// Use --indent-size to set the number of spaces to use as indentation.
-    #[arg(long)]
+    #[arg(long, overrides_with = "use_tabs")]
     use_spaces: bool,
 
-    /// Set how many spaces to use for indentation.
+    /// Use tabs for indentation, overriding `use_spaces` in configuration
+    /// files.
+    #[arg(long, overrides_with = "use_spaces")]
+    use_tabs: bool,
+
+    /// Set how many spaces to use for indentation [default: 4].
     ///
     /// Has no effect without the --use-spaces flag.
-    #[arg(long, default_value = "4", value_name = "NUM")]
-    indent_size: usize,
+    #[arg(long, value_name = "NUM")]
+    indent_size: Option<usize>,
+
+    /// Set the maximum line width [default: 100].
+    ///
+    /// Longer lines get wrapped when possible, breaking argument and parameter
+    /// lists, arrays, dictionaries, and boolean chains inside of parentheses,
+    /// starting with the outermost ones. Set to 0 to disable line wrapping.
+    #[arg(long, value_name = "NUM")]
+    line_width: Option<usize>,
+
+    /// Only format the given lines, leaving the rest of the code unchanged.
+    ///
+    /// Line numbers start at 1 and both ends are included. The range grows to
+    /// cover complete top-level statements (functions, classes, variables,
+    /// etc.). Repeat the option to format multiple ranges. Only works with a
+    /// single input file or with standard input.
+    #[arg(long, value_name = "START:END", conflicts_with = "reorder_code")]
+    lines: Vec<LineRange>,
 
     /// Reorder code to follow the official GDScript style guide.
     ///
@@ -88,9 +147,13 @@ struct Args {
     /// virtual methods, public methods, pseudo-private methods, and sub-classes.
     ///
     /// If enabled, reordering happens after formatting the code.
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_reorder_code")]
     reorder_code: bool,
 
+    /// Don't reorder code, overriding `reorder_code` in configuration files.
+    #[arg(long, overrides_with = "reorder_code")]
+    no_reorder_code: bool,
+
     /// Enable safe mode.
     ///
     /// This mode ensures that after formatting, the code still has the same
@@ -99,19 +162,76 @@ struct Args {
     ///
     /// This offers a good amount protection against the formatter failing
     /// on new syntax at the cost of a small little extra running time.
-    /// Currently incompatible with --reorder-code.
+    /// With --reorder-code, top-level declarations can change order, but each
+    /// one must keep its structure, and every comment must still be in the
+    /// output.
     ///
     /// WARNING: this is not a perfect solution. Some rare edge cases may still
     /// lead to syntax changes.
-    #[arg(short, long, conflicts_with = "reorder_code")]
+    #[arg(short, long, overrides_with = "no_safe")]
     safe: bool,
+
+    /// Disable safe mode, overriding `safe` in configuration files.
+    #[arg(long, overrides_with = "safe")]
+    no_safe: bool,
+
+    /// Enable strict safe mode.
+    ///
+    /// Like --safe, but the identifiers, literals, operators, and strings must
+    /// also keep the same text after formatting. Only whitespace, comment
+    /// positions, and trailing commas and semicolons can change.
+    #[arg(long, overrides_with = "no_strict_safe")]
+    strict_safe: bool,
+
+    /// Disable strict safe mode, overriding `strict_safe` in configuration
+    /// files.
+    #[arg(long, overrides_with = "strict_safe")]
+    no_strict_safe: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum DiffFormat {
+    /// One unified diff per file, like `git diff`
+    Unified,
+    /// A JSON array listing the changed line ranges of each file
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum LintFormat {
+    /// One line per issue: path:line:rule:severity: message
+    Text,
+    /// A JSON array of issues with their start and end positions
+    Json,
+    /// A SARIF log, for GitHub code scanning and other CI services
+    Sarif,
+    /// GitHub Actions workflow commands, shown as annotations on pull requests
+    Github,
+}
+
+/// Lowest severity of lint issues that makes the linter exit with an error.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum FailOn {
+    Error,
+    Warning,
+    Info,
 }
 
 #[derive(clap::Subcommand)]
 enum Commands {
     /// Lint GDScript files for style and convention issues
     Lint {
-        #[arg(help = "Input GDScript file(s) to lint", value_name = "FILES")]
+        #[arg(
+            help = "Input GDScript file(s) or directories to lint",
+            value_name = "PATHS"
+        )]
         input: Vec<PathBuf>,
         #[arg(
             long,
@@ -119,13 +239,91 @@ enum Commands {
             value_name = "RULES"
         )]
         disable: Option<String>,
-        #[arg(long, help = "Maximum line length allowed", default_value = "100")]
-        max_line_length: usize,
+        #[arg(long, help = "Maximum line length allowed [default: 100]")]
+        max_line_length: Option<usize>,
         #[arg(long, help = "List all available linting rules")]
         list_rules: bool,
         #[arg(long, help = "Use pretty formatting for lint output")]
         pretty: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = LintFormat::Text,
+            conflicts_with = "pretty",
+            help = "Output format of the lint issues"
+        )]
+        format: LintFormat,
+        #[arg(
+            long,
+            help = "Fix issues automatically when possible, changing the files"
+        )]
+        fix: bool,
+        #[arg(
+            long,
+            requires = "fix",
+            help = "Also apply fixes that may change the behavior of the code"
+        )]
+        unsafe_fixes: bool,
+        #[arg(long, requires = "fix", help = "Format the files after fixing them")]
+        run_formatter: bool,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "baseline",
+            help = "Record the issues found in a baseline file instead of reporting them"
+        )]
+        write_baseline: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Only report issues that are not recorded in this baseline file"
+        )]
+        baseline: Option<PathBuf>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = FailOn::Info,
+            help = "Exit with an error only if there are issues of this severity or higher"
+        )]
+        fail_on: FailOn,
     },
+    /// Print code metrics of each function in GDScript files
+    ///
+    /// Shows the length, number of parameters, nesting depth, number of return
+    /// statements, and cyclomatic complexity of each function, constructor,
+    /// and lambda.
+    Metrics {
+        #[arg(
+            help = "Input GDScript file(s) or directories to analyze",
+            value_name = "PATHS",
+            required = true
+        )]
+        input: Vec<PathBuf>,
+    },
+    /// Convert Godot 3 code to the Godot 4 syntax
+    ///
+    /// Rewrites yield() calls, export, onready, and tool keywords, setget,
+    /// networking keywords like remote, and string-based connect() calls, then
+    /// formats the files that changed. Constructs that can't be converted
+    /// automatically are listed so you can port them manually.
+    Migrate {
+        #[arg(
+            help = "Input GDScript file(s) or directories to migrate",
+            value_name = "PATHS",
+            required = true
+        )]
+        input: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Output the migrated code to stdout without changing the files"
+        )]
+        stdout: bool,
+    },
+    /// Run a language server over standard input and output
+    ///
+    /// Code editors can use it to format files and show lint issues as
+    /// diagnostics.
+    Lsp,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -138,6 +336,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let args = Args::parse();
 
+    if let Some(Commands::Lsp) = args.command {
+        return gdscript_formatter::lsp::run_server().map_err(|error| error.to_string().into());
+    }
+
+    if let Some(Commands::Metrics { input }) = &args.command {
+        return gdscript_formatter::linter::metrics::print_metrics(input);
+    }
+
+    if let Some(Commands::Migrate { input, stdout }) = &args.command {
+        return run_migration(input, *stdout);
+    }
+
     // Handle lint subcommand
     if let Some(Commands::Lint {
         input,
@@ -145,12 +355,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_line_length,
         list_rules,
         pretty,
+        format,
+        fix,
+        unsafe_fixes,
+        run_formatter,
+        write_baseline,
+        baseline,
+        fail_on,
     }) = args.command
     {
         if list_rules {
             println!("Available linting rules:");
             for rule in get_all_rule_names() {
-                println!("  {}", rule);
+                if OPT_IN_RULES.contains(&rule) {
+                    println!("  {} (opt-in)", rule);
+                } else {
+                    println!("  {}", rule);
+                }
             }
             return Ok(());
         }
@@ -162,35 +383,113 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("Use --list-rules to see all available rules");
                 std::process::exit(1);
             }
-            rules
+            Some(rules)
         } else {
-            HashSet::new()
+            None
         };
 
-        let linter_config = LinterConfig {
+        // Options passed on the command line override the configuration files
+        let lint_options = LintOptions {
             disabled_rules,
             max_line_length,
+            ..Default::default()
         };
 
-        return run_linter(input, linter_config, pretty);
+        let output_format = match format {
+            _ if pretty => OutputFormat::Pretty,
+            LintFormat::Text => OutputFormat::Text,
+            LintFormat::Json => OutputFormat::Json,
+            LintFormat::Sarif => OutputFormat::Sarif,
+            LintFormat::Github => OutputFormat::Github,
+        };
+        let fix_options = fix.then_some(FixOptions {
+            unsafe_fixes,
+            run_formatter,
+        });
+        let fail_on = match fail_on {
+            FailOn::Error => LintSeverity::Error,
+            FailOn::Warning => LintSeverity::Warning,
+            FailOn::Info => LintSeverity::Info,
+        };
+        let baseline_mode = match (write_baseline, baseline) {
+            (Some(path), _) => Some(BaselineMode::Write(path)),
+            (None, Some(path)) => Some(BaselineMode::Compare(path)),
+            (None, None) => None,
+        };
+        return run_linter(
+            input,
+            &lint_options,
+            output_format,
+            fix_options.as_ref(),
+            baseline_mode.as_ref(),
+            fail_on,
+        );
     }
 
-    let config = FormatterConfig {
+    // Each boolean option has a flag to turn it on and one to turn it off, and
+    // we only override the configuration files when one of them is set
+    let format_options = FormatOptions {
+        use_spaces: get_flag_pair_value(args.use_spaces, args.use_tabs),
         indent_size: args.indent_size,
-        use_spaces: args.use_spaces,
-        reorder_code: args.reorder_code,
-        safe: args.safe,
+        reorder_code: get_flag_pair_value(args.reorder_code, args.no_reorder_code),
+        safe: get_flag_pair_value(args.safe, args.no_safe),
+        strict_safe: get_flag_pair_value(args.strict_safe, args.no_strict_safe),
+        line_width: args.line_width,
+    };
+    let mut config_resolver = ConfigResolver::new();
+    // Formats the whole content, or only the lines passed with --lines
+    let format_content = |content: &str, config: &FormatterConfig| {
+        if args.lines.is_empty() {
+            format_gdscript_with_config(content, config)
+        } else {
+            format_gdscript_lines(content, config, &args.lines)
+        }
+    };
+    let use_colors = match args.color {
+        ColorChoice::Auto => io::stdout().is_terminal(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
     };
 
     if args.input.is_empty() {
+        let config = config_resolver.formatter_config(&env::current_dir()?, &format_options)?;
+
         let mut input_content = String::new();
-        io::stdin()
-            .read_to_string(&mut input_content)
-            .map_err(|error| format!("Failed to read from stdin: {}", error))?;
+        if let Err(error) = io::stdin().read_to_string(&mut input_content) {
+            eprintln!("Error: Failed to read from stdin: {}", error);
+            std::process::exit(get_read_error_exit_code(&error));
+        }
 
-        let formatted_content = format_gdscript_with_config(&input_content, &config)?;
+        let formatted_content = match format_content(&input_content, &config) {
+            Ok(formatted_content) => formatted_content,
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                std::process::exit(error.exit_code());
+            }
+        };
 
-        if args.check {
+        if args.diff {
+            let is_formatted = input_content == formatted_content;
+            match args.diff_format {
+                DiffFormat::Unified => print!(
+                    "{}",
+                    unified_diff("stdin", &input_content, &formatted_content, use_colors)
+                ),
+                DiffFormat::Json => {
+                    let mut all_changes = Vec::new();
+                    if !is_formatted {
+                        all_changes.push(FileChanges {
+                            file: "stdin".to_string(),
+                            changes: changed_line_ranges(&input_content, &formatted_content),
+                        });
+                    }
+                    println!("{}", serde_json::to_string_pretty(&all_changes)?);
+                }
+            }
+            if !is_formatted {
+                std::process::exit(1);
+            }
+        } else if args.check {
             if input_content != formatted_content {
                 eprintln!("The input passed via stdin is not formatted");
                 std::process::exit(1);
@@ -204,20 +503,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    let input_gdscript_files: Vec<&PathBuf> = args
-        .input
-        .iter()
-        .filter(|path| path.extension().map_or(false, |ext| ext == "gd"))
-        .collect();
+    let input_gdscript_files = find_gdscript_files(&args.input)?;
 
     if input_gdscript_files.is_empty() {
         eprintln!(
-            "Error: No GDScript files found in the arguments provided. Please provide at least one .gd file."
+            "Error: No GDScript files found in the arguments provided. Please provide at least one .gd file or a directory containing .gd files."
         );
         std::process::exit(1);
     }
 
     let total_files = input_gdscript_files.len();
+    if !args.lines.is_empty() && total_files > 1 {
+        eprintln!("Error: --lines can only be used with a single input file.");
+        std::process::exit(1);
+    }
+
+    // We resolve the configuration of each file before formatting as files
+    // from the same folder share the same configuration files
+    let file_configs = input_gdscript_files
+        .iter()
+        .map(|file_path| config_resolver.formatter_config(file_path, &format_options))
+        .collect::<Result<Vec<_>, _>>()?;
 
     eprint!(
         "Formatting {} file{}...",
@@ -229,24 +535,32 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // We use the rayon library to automatically process files in parallel for
     // us. The formatter runs largely single threaded so this speeds things up a
     // lot on multi-core CPUs
-    let outputs: Vec<Result<FormatterOutput, String>> = input_gdscript_files
+    // Errors come with the exit code to use, which depends on the kind of error
+    let outputs: Vec<Result<FormatterOutput, (String, i32)>> = input_gdscript_files
         .par_iter()
         .enumerate()
         .map(|(index, file_path)| {
             let input_content = fs::read_to_string(file_path).map_err(|error| {
-                format!("Failed to read file {}: {}", file_path.display(), error)
+                (
+                    format!("Failed to read file {}: {}", file_path.display(), error),
+                    get_read_error_exit_code(&error),
+                )
             })?;
 
             let formatted_content =
-                format_gdscript_with_config(&input_content, &config).map_err(|error| {
-                    format!("Failed to format file {}: {}", file_path.display(), error)
+                format_content(&input_content, &file_configs[index]).map_err(|error| {
+                    (
+                        format!("Failed to format file {}: {}", file_path.display(), error),
+                        error.exit_code(),
+                    )
                 })?;
 
             let is_formatted = input_content == formatted_content;
 
             Ok(FormatterOutput {
                 index,
-                file_path: (*file_path).clone(),
+                file_path: file_path.clone(),
+                input_content,
                 formatted_content,
                 is_formatted,
             })
@@ -265,10 +579,41 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // If true, all input files were already formatted (used for check mode)
     let mut all_formatted = true;
+    // Changed line ranges of each file, printed at the end with --diff-format=json
+    let mut all_changes = Vec::new();
     for output in sorted_outputs {
         match output {
             Ok(output) => {
-                if args.check {
+                if args.diff {
+                    if output.is_formatted {
+                        continue;
+                    }
+                    all_formatted = false;
+                    let file_path = output.file_path.display().to_string();
+                    match args.diff_format {
+                        DiffFormat::Unified => {
+                            // Clear the progress message before printing the diff
+                            terminal_clear_line();
+                            eprint!("\r");
+                            print!(
+                                "{}",
+                                unified_diff(
+                                    &file_path,
+                                    &output.input_content,
+                                    &output.formatted_content,
+                                    use_colors
+                                )
+                            );
+                        }
+                        DiffFormat::Json => all_changes.push(FileChanges {
+                            changes: changed_line_ranges(
+                                &output.input_content,
+                                &output.formatted_content,
+                            ),
+                            file: file_path,
+                        }),
+                    }
+                } else if args.check {
                     if !output.is_formatted {
                         all_formatted = false;
                     }
@@ -293,13 +638,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     })?;
                 }
             }
-            Err(error_msg) => {
-                return Err(error_msg.into());
+            Err((error_msg, exit_code)) => {
+                terminal_clear_line();
+                eprintln!("\rError: {}", error_msg);
+                std::process::exit(exit_code);
             }
         }
     }
 
-    if args.check {
+    if args.diff && args.diff_format == DiffFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&all_changes)?);
+    }
+
+    if args.check || args.diff {
         if all_formatted {
             terminal_clear_line();
             eprintln!("\rAll {} file(s) are formatted", total_files);
@@ -322,19 +673,116 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 fn run_linter(
     input_files: Vec<PathBuf>,
-    config: LinterConfig,
-    pretty: bool,
+    lint_options: &LintOptions,
+    output_format: OutputFormat,
+    fix_options: Option<&FixOptions>,
+    baseline_mode: Option<&BaselineMode>,
+    fail_on: LintSeverity,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut linter = gdscript_formatter::linter::GDScriptLinter::new(config)?;
-    let has_issues = linter.lint_files(input_files, pretty)?;
+    let mut linter = gdscript_formatter::linter::GDScriptLinter::new(Default::default())?;
+    let highest_severity = linter.lint_files(
+        input_files,
+        lint_options,
+        output_format,
+        fix_options,
+        baseline_mode,
+    )?;
+
+    if highest_severity.is_some_and(|severity| severity >= fail_on) {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
 
-    if has_issues {
+fn run_migration(input_files: &[PathBuf], stdout: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let gdscript_files = find_gdscript_files(input_files)?;
+    if gdscript_files.is_empty() {
+        eprintln!(
+            "Error: No GDScript files found in the arguments provided. Please provide at least one .gd file or a directory containing .gd files."
+        );
         std::process::exit(1);
     }
 
+    let mut config_resolver = ConfigResolver::new();
+    let mut migrated_files_count = 0;
+    let mut issues_count = 0;
+    for file_path in &gdscript_files {
+        let content = fs::read_to_string(file_path)
+            .map_err(|error| format!("Failed to read file {}: {}", file_path.display(), error))?;
+        let result = migrate_gdscript(&content).map_err(|error| {
+            format!("Failed to migrate file {}: {}", file_path.display(), error)
+        })?;
+
+        for issue in &result.issues {
+            eprintln!(
+                "{}:{}:{}: {}",
+                file_path.display(),
+                issue.line,
+                issue.column,
+                issue.message
+            );
+        }
+        issues_count += result.issues.len();
+
+        let mut migrated_content = result.code;
+        if migrated_content != content {
+            let config = config_resolver.formatter_config(file_path, &FormatOptions::default())?;
+            migrated_content =
+                format_gdscript_with_config(&migrated_content, &config).map_err(|error| {
+                    format!("Failed to format file {}: {}", file_path.display(), error)
+                })?;
+            migrated_files_count += 1;
+        }
+
+        if stdout {
+            if gdscript_files.len() > 1 {
+                println!("#--file:{}", file_path.display());
+            }
+            print!("{}", migrated_content);
+        } else if migrated_content != content {
+            fs::write(file_path, &migrated_content).map_err(|error| {
+                format!("Failed to write to file {}: {}", file_path.display(), error)
+            })?;
+        }
+    }
+
+    eprintln!(
+        "Migrated {} file{}",
+        migrated_files_count,
+        if migrated_files_count == 1 { "" } else { "s" }
+    );
+    if issues_count > 0 {
+        eprintln!(
+            "{} construct{} to review or migrate manually",
+            issues_count,
+            if issues_count == 1 { "" } else { "s" }
+        );
+    }
     Ok(())
 }
 
+/// Returns the value of a boolean option that has a flag to turn it on and
+/// one to turn it off, or None if neither flag is set. Clap only keeps the
+/// last flag of the pair.
+fn get_flag_pair_value(enable: bool, disable: bool) -> Option<bool> {
+    match (enable, disable) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the exit code for an error reading the code to format. Code that
+/// isn't valid UTF-8 gets the exit code of `FormatError::Utf8`.
+fn get_read_error_exit_code(error: &io::Error) -> i32 {
+    if error.kind() == io::ErrorKind::InvalidData {
+        FormatError::Utf8(error.to_string()).exit_code()
+    } else {
+        1
+    }
+}
+
 fn terminal_clear_line() {
     eprint!("\r{}", " ".repeat(80));
 }
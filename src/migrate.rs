@@ -0,0 +1,412 @@
+//! This module converts Godot 3 code to the Godot 4 syntax.
+//!
+//! Like the reorder module, it parses the code with tree-sitter, but instead
+//! of moving declarations around, it replaces the Godot 3 constructs that the
+//! godot3-syntax lint rule detects:
+//!
+//! - `yield(object, "signal")` becomes `await object.signal`, and
+//!   `yield(function(), "completed")` becomes `await function()`
+//! - `export(int) var x` becomes `@export var x: int`, and export hints
+//!   become annotations like `@export_range()` or `@export_file()`
+//! - `onready var` becomes `@onready var`, and `tool` becomes `@tool`
+//! - `setget setter, getter` becomes `: set = setter, get = getter`
+//! - `remote func` and other networking keywords become `@rpc` annotations
+//! - `connect("signal", target, "method")` becomes
+//!   `signal.connect(target.method)`
+//!
+//! Constructs that have no direct equivalent are left unchanged and returned
+//! as issues, so that users can port them manually. So are conversions that
+//! change how the code behaves, like `setget`. The caller is expected to run
+//! the formatter on the result.
+use tree_sitter::{Node, Parser};
+
+use crate::linter::fix::TextEdit;
+use crate::linter::lib::{get_line_column, get_node_text};
+use crate::linter::rules::godot3_syntax::{
+    get_await_replacement, get_call_arguments, get_connect_replacement, get_export_conversion,
+    get_rpc_annotation,
+};
+use crate::linter::static_typing::has_inferred_type;
+
+/// Maximum number of times we parse the code and apply the replacements. We
+/// skip replacements that overlap with others, like nested calls, and apply
+/// them in the next pass.
+const MAX_MIGRATION_PASSES: usize = 10;
+
+/// A Godot 3 construct that couldn't be converted automatically, or that
+/// behaves differently after conversion. The line and column are positions
+/// in the input code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationIssue {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationResult {
+    pub code: String,
+    pub issues: Vec<MigrationIssue>,
+}
+
+/// Converts the Godot 3 syntax found in `content` to Godot 4.
+pub fn migrate_gdscript(content: &str) -> Result<MigrationResult, Box<dyn std::error::Error>> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_gdscript::LANGUAGE.into())
+        .map_err(|e| format!("Failed to set language: {}", e))?;
+
+    let mut code = content.to_string();
+    let mut issues = Vec::new();
+    let mut pass = 0;
+    loop {
+        let tree = parser
+            .parse(&code, None)
+            .ok_or("Failed to parse GDScript code")?;
+        let mut migration = Migration {
+            source_code: &code,
+            edits: Vec::new(),
+            issues: Vec::new(),
+        };
+        migration.visit(&tree.root_node());
+        // Later passes find the same issues, but at positions in the code
+        // that we already changed
+        if pass == 0 {
+            issues = migration.issues;
+        }
+
+        pass += 1;
+        if migration.edits.is_empty() || pass == MAX_MIGRATION_PASSES {
+            return Ok(MigrationResult { code, issues });
+        }
+        code = apply_edits(&code, migration.edits);
+    }
+}
+
+/// Collects the edits and issues of one migration pass.
+struct Migration<'a> {
+    source_code: &'a str,
+    edits: Vec<TextEdit>,
+    issues: Vec<MigrationIssue>,
+}
+
+impl Migration<'_> {
+    fn visit(&mut self, node: &Node) {
+        match node.kind() {
+            "tool_statement" => self.edits.push(TextEdit::replace_node(node, "@tool")),
+            "onready_variable_statement" => {
+                if let Some(keyword_node) = node.child(0) {
+                    self.edits
+                        .push(TextEdit::replace_node(&keyword_node, "@onready"));
+                }
+            }
+            "export_variable_statement" => self.migrate_export(node),
+            "setget" if get_node_text(node, self.source_code).starts_with("setget") => {
+                self.migrate_setget(node)
+            }
+            "remote_keyword" => self.migrate_remote_keyword(node),
+            "call" | "attribute_call" => self.migrate_call(node),
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(&child);
+        }
+    }
+
+    fn report(&mut self, node: &Node, message: String) {
+        let (line, column) = get_line_column(node);
+        self.issues.push(MigrationIssue {
+            line,
+            column,
+            message,
+        });
+    }
+
+    /// Replaces `export(hint)` with an annotation, and adds the type from
+    /// the hint after the variable name.
+    fn migrate_export(&mut self, node: &Node) {
+        let mut cursor = node.walk();
+        let var_node = node
+            .children(&mut cursor)
+            .find(|child| child.kind() == "var");
+        let (Some(conversion), Some(var_node)) =
+            (get_export_conversion(node, self.source_code), var_node)
+        else {
+            let head = node
+                .child_by_field_name("name")
+                .map_or(get_node_text(node, self.source_code), |name_node| {
+                    &self.source_code[node.start_byte()..name_node.start_byte()]
+                });
+            self.report(
+                node,
+                format!(
+                    "Can't convert '{}' automatically. Use one of Godot 4's @export annotations instead",
+                    head.trim()
+                ),
+            );
+            return;
+        };
+
+        self.edits.push(TextEdit::replace(
+            node.start_byte()..var_node.start_byte(),
+            format!("{} ", conversion.annotation),
+        ));
+        if let (Some(type_hint), Some(name_node)) =
+            (conversion.type_hint, node.child_by_field_name("name"))
+            && node.child_by_field_name("type").is_none()
+            && !has_inferred_type(node)
+        {
+            self.edits.push(TextEdit::insert(
+                name_node.end_byte(),
+                format!(": {}", type_hint),
+            ));
+        }
+    }
+
+    /// Replaces `setget setter, getter` with `: set = setter, get = getter`.
+    /// We report the conversion too, as Godot 4 calls the accessors when the
+    /// class uses the variable directly, while Godot 3 only called them with
+    /// `self.`.
+    fn migrate_setget(&mut self, node: &Node) {
+        let text = get_node_text(node, self.source_code);
+        let mut functions = text["setget".len()..].split(',').map(str::trim);
+        let setter = functions.next().filter(|setter| !setter.is_empty());
+        let getter = functions.next().filter(|getter| !getter.is_empty());
+
+        let accessors: Vec<String> = [("set", setter), ("get", getter)]
+            .into_iter()
+            .filter_map(|(keyword, function)| {
+                function.map(|function| format!("{} = {}", keyword, function))
+            })
+            .collect();
+        if accessors.is_empty() {
+            self.report(
+                node,
+                "Can't convert an empty 'setget' automatically".to_string(),
+            );
+            return;
+        }
+
+        // The colon goes right after the previous part of the declaration
+        let start = node
+            .prev_sibling()
+            .map_or(node.start_byte(), |sibling| sibling.end_byte());
+        self.edits.push(TextEdit::replace(
+            start..node.end_byte(),
+            format!(": {}", accessors.join(", ")),
+        ));
+
+        let variable = node
+            .parent()
+            .and_then(|parent| parent.child_by_field_name("name"))
+            .map_or("the variable", |name_node| {
+                get_node_text(&name_node, self.source_code)
+            });
+        self.report(
+            node,
+            format!(
+                "Converted 'setget' to '{}'. Godot 4 calls these functions even when the class uses '{}' without 'self.', so code that relied on bypassing them needs to change",
+                accessors.join(", "),
+                variable
+            ),
+        );
+    }
+
+    /// Replaces a networking keyword before a function with an `@rpc`
+    /// annotation on the line above it.
+    fn migrate_remote_keyword(&mut self, node: &Node) {
+        let keyword = get_node_text(node, self.source_code);
+        let next_sibling = node.next_sibling();
+        let (Some(parent), Some(next_sibling)) = (node.parent(), next_sibling) else {
+            return;
+        };
+        if parent.kind() != "function_definition" {
+            self.report(
+                node,
+                format!(
+                    "Can't convert '{}' variables automatically. Godot 4 doesn't synchronize variables with keywords: use a MultiplayerSynchronizer node or RPCs instead",
+                    keyword
+                ),
+            );
+            return;
+        }
+
+        let line_start = self.source_code[..parent.start_byte()]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let indentation = &self.source_code[line_start..parent.start_byte()];
+        self.edits.push(TextEdit::replace(
+            node.start_byte()..next_sibling.start_byte(),
+            format!("{}\n{}", get_rpc_annotation(keyword), indentation),
+        ));
+    }
+
+    fn migrate_call(&mut self, node: &Node) {
+        let Some(function_node) = node.child(0) else {
+            return;
+        };
+        let arguments = get_call_arguments(node);
+        match get_node_text(&function_node, self.source_code) {
+            "yield" if node.kind() == "call" => {
+                match get_await_replacement(&arguments, self.source_code) {
+                    Some(replacement) => {
+                        self.edits.push(TextEdit::replace_node(node, replacement))
+                    }
+                    None => self.report(
+                        node,
+                        "Can't convert this 'yield()' call automatically. Use 'await' with a signal or a coroutine instead".to_string(),
+                    ),
+                }
+            }
+            "connect" => {
+                if let Some(replacement) = get_connect_replacement(&arguments, self.source_code) {
+                    self.edits.push(TextEdit::replace_node(node, replacement));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Applies the edits that don't overlap with each other, in a single pass.
+fn apply_edits(source_code: &str, mut edits: Vec<TextEdit>) -> String {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut output = String::with_capacity(source_code.len());
+    let mut position = 0;
+    for edit in edits {
+        if edit.range.start < position {
+            continue;
+        }
+        output.push_str(&source_code[position..edit.range.start]);
+        output.push_str(&edit.replacement);
+        position = edit.range.end;
+    }
+    output.push_str(&source_code[position..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_gdscript() {
+        let source_code = r#"tool
+extends Node
+
+signal health_changed
+
+export(int, 0, 100) var health = 100 setget set_health
+export var title = "Player"
+onready var label = $Label
+
+
+remote func sync_position(new_position):
+	print(new_position)
+
+
+func _ready():
+	connect("health_changed", self, "on_health_changed")
+	label.connect("pressed", self, "on_pressed", [1])
+	yield(get_tree(), "idle_frame")
+	yield(label.play(), "completed")
+
+
+func set_health(value):
+	health = value
+"#;
+        let expected = r#"@tool
+extends Node
+
+signal health_changed
+
+@export_range(0, 100) var health: int = 100: set = set_health
+@export var title = "Player"
+@onready var label = $Label
+
+
+@rpc("any_peer")
+func sync_position(new_position):
+	print(new_position)
+
+
+func _ready():
+	health_changed.connect(on_health_changed)
+	label.pressed.connect(on_pressed.bind(1))
+	await get_tree().process_frame
+	await label.play()
+
+
+func set_health(value):
+	health = value
+"#;
+        let result = migrate_gdscript(source_code).unwrap();
+        assert_eq!(result.code, expected);
+        assert_eq!(
+            result.issues,
+            vec![MigrationIssue {
+                line: 6,
+                column: 38,
+                message: "Converted 'setget' to 'set = set_health'. Godot 4 calls these functions even when the class uses 'health' without 'self.', so code that relied on bypassing them needs to change".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_migrate_rpc_keywords() {
+        let source_code = "master func request_respawn():\n\tpass\n\n\nmastersync func respawn():\n\tpass\n\n\npuppetsync func update():\n\tpass\n";
+        let expected = "@rpc(\"any_peer\")\nfunc request_respawn():\n\tpass\n\n\n@rpc(\"any_peer\", \"call_local\")\nfunc respawn():\n\tpass\n\n\n@rpc(\"call_local\")\nfunc update():\n\tpass\n";
+        let result = migrate_gdscript(source_code).unwrap();
+        assert_eq!(result.code, expected);
+        assert!(result.issues.is_empty());
+        assert_eq!(
+            get_rpc_annotation("sync"),
+            "@rpc(\"any_peer\", \"call_local\")"
+        );
+    }
+
+    #[test]
+    fn test_migrate_reports_unsupported_constructs() {
+        let source_code = "export(float, EXP, 100, 1000) var volume = 100\nremote var position\n\n\nfunc wait():\n\tyield()\n";
+        let result = migrate_gdscript(source_code).unwrap();
+        assert_eq!(result.code, source_code);
+        let lines: Vec<usize> = result.issues.iter().map(|issue| issue.line).collect();
+        assert_eq!(lines, vec![1, 2, 6]);
+        assert_eq!(
+            result.issues[0].message,
+            "Can't convert 'export(float, EXP, 100, 1000) var' automatically. Use one of Godot 4's @export annotations instead"
+        );
+    }
+
+    #[test]
+    fn test_migrate_issue_positions() {
+        // Converting the keyword adds a line, but the issue points at the
+        // input code
+        let source_code = "remote func sync_position():\n\tpass\n\n\nremote var position\n";
+        let result = migrate_gdscript(source_code).unwrap();
+        assert!(
+            result
+                .code
+                .starts_with("@rpc(\"any_peer\")\nfunc sync_position():")
+        );
+        let positions: Vec<(usize, usize)> = result
+            .issues
+            .iter()
+            .map(|issue| (issue.line, issue.column))
+            .collect();
+        assert_eq!(positions, vec![(5, 1)]);
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let edits = vec![
+            TextEdit::replace(4..7, "b"),
+            TextEdit::replace(0..3, "x"),
+            TextEdit::replace(5..6, "skipped"),
+        ];
+        assert_eq!(apply_edits("abc def ghi", edits), "x b ghi");
+    }
+}
@@ -9,13 +9,13 @@
 //! a code editor command or task when you're met with a messy file.
 use tree_sitter::{Node, Query, QueryCursor, StreamingIterator, Tree};
 
+use crate::error::FormatError;
+
 /// This method parses the GDScript content, extracts top-level elements,
 /// and reorders them according to the GDScript style guide.
-pub fn reorder_gdscript_elements(
-    tree: &Tree,
-    content: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let tokens = extract_tokens_to_reorder(&tree, content)?;
+pub fn reorder_gdscript_elements(tree: &Tree, content: &str) -> Result<String, FormatError> {
+    let tokens = extract_tokens_to_reorder(&tree, content)
+        .map_err(|error| FormatError::Reorder(error.to_string()))?;
     let ordered_elements = sort_gdscript_tokens(tokens);
     let reordered_content = build_reordered_code(ordered_elements, content);
 
@@ -218,7 +218,7 @@ fn extract_tokens_to_reorder(
     content: &str,
 ) -> Result<Vec<GDScriptTokensWithComments>, Box<dyn std::error::Error>> {
     let root = tree.root_node();
-    let mut elements = Vec::new();
+    let mut elements: Vec<GDScriptTokensWithComments> = Vec::new();
 
     // This query covers all top-level elements (direct children of source)
     // We need to capture everything so nothing gets lost
@@ -324,6 +324,17 @@ fn extract_tokens_to_reorder(
                 // This may look inefficient but in practice it should not have much impact
                 if class_docstring_comments_rows.contains(&node.start_position().row) {
                     continue;
+                }
+                // Inline comments stay at the end of the line of the element
+                // they follow, as they can be directives like `# fmt: skip`
+                if let Some(previous) = elements.last_mut()
+                    && previous.end_byte > 0
+                    && previous.end_byte <= node.start_byte()
+                    && !content[previous.end_byte..node.start_byte()].contains('\n')
+                {
+                    previous.original_text =
+                        content[previous.start_byte..node.end_byte()].to_string();
+                    previous.end_byte = node.end_byte();
                 } else {
                     pending_comments.push(text);
                 }
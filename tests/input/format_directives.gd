@@ -0,0 +1,20 @@
+# fmt: off
+const GRID = [
+	1, 0, 0,
+	0, 1, 0,
+	0, 0, 1,
+]
+# fmt: on
+var speed=10
+var offsets = [ 1,2 ] # fmt: skip
+
+
+func _ready():
+	var a=1
+	# fmt: off
+	var  table = {
+		"a":   1,
+		"bb":  2,
+	}
+	# fmt: on
+	print(a)
@@ -0,0 +1,8 @@
+# Lines longer than 100 characters get wrapped, starting with the outermost construct.
+func create_character(character_name: String, health: int, speed: float, inventory: Array, is_player: bool) -> void:
+	var fits = some_function_with_a_long_name(first_argument, second_argument, third_argument, 42)
+	var result = some_function_with_a_long_name(first_argument, second_argument, third_argument, 42, true)
+	var items = ["sword", "shield", "health potion", "mana potion", "map of the kingdom", "torch", "rope"]
+	if (character.position.x > minimum_position_x and character.position.x < maximum_position_x and is_on_floor()):
+		pass
+	print(build_message(character_name, "has been created with", health, "health points and a speed of", speed))
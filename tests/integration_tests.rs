@@ -1,5 +1,8 @@
 use gdscript_formatter::FormatterConfig;
-use gdscript_formatter::formatter::format_gdscript_with_config;
+use gdscript_formatter::error::FormatError;
+use gdscript_formatter::formatter::{
+    LineRange, format_gdscript_lines, format_gdscript_with_config,
+};
 use gdscript_formatter::linter::{GDScriptLinter, LinterConfig};
 use similar::{ChangeTag, TextDiff};
 use std::fs;
@@ -51,6 +54,7 @@ fn test_reorder_file(file_path: &Path) {
         file_path,
         &FormatterConfig {
             reorder_code: true,
+            safe: true,
             ..Default::default()
         },
         true,
@@ -93,10 +97,7 @@ fn test_lint_file(file_path: &Path) {
             relative_path,
             issue.line,
             issue.rule,
-            match issue.severity {
-                gdscript_formatter::linter::LintSeverity::Error => "error",
-                gdscript_formatter::linter::LintSeverity::Warning => "warning",
-            },
+            issue.severity.as_str(),
             issue.message
         ));
     }
@@ -152,3 +153,65 @@ fn test_file_with_config(file_path: &Path, config: &FormatterConfig, check_idemp
         );
     }
 }
+
+#[test]
+fn test_format_line_range() {
+    let input = "var a=1\n\n\nfunc a( ):\n\tvar x=1\n\n\nfunc b( ):\n\tvar y=2\n";
+    let expected = "var a=1\n\n\nfunc a( ):\n\tvar x=1\n\n\nfunc b():\n\tvar y = 2\n";
+
+    // The range grows to cover the whole function that contains the line
+    let result = format_gdscript_lines(
+        input,
+        &FormatterConfig::default(),
+        &[LineRange { start: 9, end: 9 }],
+    )
+    .expect("Failed to format line range");
+    assert_formatted_eq(
+        &result,
+        expected,
+        Path::new("test_format_line_range"),
+        "Formatting a line range changed code outside of the range",
+    );
+}
+
+#[test]
+fn test_format_invalid_line_range() {
+    let input = "var a=1\n";
+    for range in [
+        LineRange { start: 0, end: 1 },
+        LineRange { start: 2, end: 1 },
+    ] {
+        let result = format_gdscript_lines(input, &FormatterConfig::default(), &[range]);
+        assert_eq!(
+            result,
+            Err(FormatError::InvalidLineRange {
+                start: range.start,
+                end: range.end,
+            })
+        );
+    }
+}
+
+#[test]
+fn test_format_line_range_with_formatting_off() {
+    let input = "var a=1\n\n\n# fmt: off\nvar grid=[\n\t1,0,\n\t0,1]\n# fmt: on\n\n\nfunc b( ):\n\tvar y=2\n";
+    let expected = "var a=1\n\n\n# fmt: off\nvar grid=[\n\t1,0,\n\t0,1]\n# fmt: on\n\n\nfunc b():\n\tvar y = 2\n";
+
+    // A range inside a region with formatting turned off grows to cover the
+    // whole region, which stays unformatted
+    let result = format_gdscript_lines(
+        input,
+        &FormatterConfig::default(),
+        &[
+            LineRange { start: 6, end: 6 },
+            LineRange { start: 12, end: 12 },
+        ],
+    )
+    .expect("Failed to format line ranges");
+    assert_formatted_eq(
+        &result,
+        expected,
+        Path::new("test_format_line_range_with_formatting_off"),
+        "Formatting a line range changed code with formatting turned off",
+    );
+}
@@ -0,0 +1,35 @@
+#region Good
+
+func simple(value):
+	if value > 0 and value < 10:
+		return "small"
+	return "large"
+
+#endregion
+
+#region Bad
+
+func too_complex(value):
+	if value == 1 or value == 2 or value == 3:
+		print("low")
+	elif value == 4 or value == 5 or value == 6:
+		print("mid")
+	for i in value:
+		while i > 0 and i < 10:
+			i -= 1
+	return "big" if value > 100 else "small"
+
+
+func complex_lambda():
+	var classify = func(value):
+		var label = "big" if value > 100 else "small"
+		if value == 1 or value == 2 or value == 3:
+			label = "low"
+		elif value == 4 or value == 5 or value == 6:
+			label = "mid"
+		elif value == 7 or value == 8 or value == 9:
+			label = "high"
+		return label
+	print(classify.call(1))
+
+#endregion
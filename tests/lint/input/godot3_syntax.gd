@@ -0,0 +1,50 @@
+tool
+extends Node
+
+signal health_changed
+
+export(int) var speed = 10
+export var title = "Player"
+onready var label = $Label
+var health = 100 setget set_health, get_health
+
+
+remote func sync_position(new_position):
+	print(new_position)
+
+
+puppet func update_health(value):
+	health = value
+
+
+master func request_respawn():
+	pass
+
+
+mastersync func respawn():
+	pass
+
+
+func _ready():
+	connect("health_changed", self, "on_health_changed")
+	label.connect("pressed", self, "on_pressed", [1])
+	yield(get_tree(), "idle_frame")
+	yield(get_health(), "completed")
+	health_changed.connect(on_health_changed)
+	await get_tree().process_frame
+
+
+func set_health(value):
+	health = value
+
+
+func get_health():
+	return health
+
+
+func on_health_changed():
+	pass
+
+
+func on_pressed(value):
+	print(value)
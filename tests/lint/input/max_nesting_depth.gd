@@ -0,0 +1,37 @@
+#region Good
+
+func shallow(items):
+	for item in items:
+		if item:
+			while item > 0:
+				match item:
+					1:
+						print(item)
+				item -= 1
+
+#endregion
+
+#region Bad
+
+func too_deep(items):
+	for item in items:
+		if item:
+			while item > 0:
+				match item:
+					1:
+						if item:
+							print(item)
+				item -= 1
+
+
+func deep_lambda():
+	var callback = func(items):
+		for item in items:
+			if item:
+				for other in item:
+					while other:
+						if other > 1:
+							print(other)
+	callback.call([])
+
+#endregion
@@ -0,0 +1,17 @@
+#region Good
+
+func few_parameters(a, b, c, d, e, f, g, h, i, j):
+	print(a, b, c, d, e, f, g, h, i, j)
+
+#endregion
+
+#region Bad
+
+func too_many_parameters(a, b, c, d, e, f, g, h, i, j, k):
+	print(a, b, c, d, e, f, g, h, i, j, k)
+
+
+func _init(a, b, c, d, e, f, g, h, i, j, k):
+	print(a, b, c, d, e, f, g, h, i, j, k)
+
+#endregion
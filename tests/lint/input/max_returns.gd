@@ -0,0 +1,31 @@
+#region Good
+
+func few_returns(value):
+	if value == 0:
+		return "zero"
+	if value < 0:
+		return "negative"
+	return "positive"
+
+#endregion
+
+#region Bad
+
+func too_many_returns(value):
+	match value:
+		0:
+			return "zero"
+		1:
+			return "one"
+		2:
+			return "two"
+		3:
+			return "three"
+		4:
+			return "four"
+		5:
+			return "five"
+		_:
+			return "many"
+
+#endregion
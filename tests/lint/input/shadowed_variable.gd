@@ -0,0 +1,44 @@
+extends Node2D
+
+#region Good
+
+var speed = 10.0
+
+
+func move(delta):
+	var distance = speed * delta
+	print(distance)
+
+
+func set_speed(new_speed):
+	speed = new_speed
+
+#endregion
+
+#region Bad
+
+func bad_local():
+	var speed = 2.0
+	print(speed)
+
+
+func bad_argument(position):
+	print(position)
+
+
+func bad_loop(items):
+	for speed in items:
+		print(speed)
+
+
+func bad_lambda():
+	var count = 0
+	var callback = func(count): return count
+	callback.call(count)
+
+
+# gdlint-ignore-next-line shadowed-variable
+func ignored(scale):
+	print(scale)
+
+#endregion
@@ -0,0 +1,25 @@
+# gdlint-disable constant-name
+const bad_one = 1
+const bad_two = 2
+# gdlint-enable constant-name
+const bad_three = 3
+
+
+class Helper:
+	func _private_method():
+		pass
+
+
+# gdlint-ignore-function private-access
+func uses_private():
+	var helper = Helper.new()
+	helper._private_method()
+
+
+func uses_private_again():
+	var helper = Helper.new()
+	helper._private_method()
+
+
+# gdlint-ignore-next-line private-access, not-a-rule
+var unused_value = 10
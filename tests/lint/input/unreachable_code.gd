@@ -0,0 +1,81 @@
+#region Good
+
+func good(value):
+	if value < 0:
+		return -1
+	return value
+
+
+func good_loop(items):
+	for item in items:
+		if item == null:
+			continue
+		print(item)
+
+
+func good_match(value):
+	match value:
+		0:
+			return "zero"
+		1:
+			return "one"
+	return "many"
+
+
+func good_while():
+	while true:
+		if randf() > 0.5:
+			break
+	print("done")
+
+
+func good_guarded_match(value):
+	match value:
+		0:
+			return 1
+		_ when value > 5:
+			return 2
+	return 3
+
+#endregion
+
+#region Bad
+
+func bad_return():
+	return 1
+	print("unreachable")
+	print("also unreachable")
+
+
+func bad_loop(items):
+	for item in items:
+		if item == null:
+			continue
+			print("unreachable")
+		break
+		print(item)
+
+
+func bad_if(value):
+	if value < 0:
+		return -1
+	else:
+		return value
+	print("unreachable")
+
+
+func bad_match(value):
+	match value:
+		0:
+			return "zero"
+		_:
+			return "other"
+	print("unreachable")
+
+
+func bad_while():
+	while true:
+		print("waiting")
+	print("unreachable")
+
+#endregion
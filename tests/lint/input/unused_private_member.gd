@@ -0,0 +1,47 @@
+extends Node
+
+#region Good
+
+@export var _exported_value = 1
+var _speed = 10.0
+var _health = 100
+var _label_text = ""
+
+
+func _ready():
+	print(_speed)
+	self._health -= 1
+	_update_label()
+
+
+func _update_label():
+	call_deferred("_refresh")
+	print(_label_text)
+
+
+func _refresh():
+	pass
+
+
+func _get_damage():
+	return 10
+
+
+var damage = _get_damage()
+
+#endregion
+
+#region Bad
+
+var _unused_value = 0
+var _shadowed_value = 0
+
+
+func _unused_method():
+	pass
+
+
+func shadow(_shadowed_value):
+	print(_shadowed_value)
+
+#endregion
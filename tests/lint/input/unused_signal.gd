@@ -0,0 +1,35 @@
+#region Good
+
+signal health_changed(health)
+signal died
+signal legacy_event
+signal connected_event
+
+
+func take_damage(amount):
+	health_changed.emit(amount)
+	if amount > 100:
+		self.died.emit()
+	emit_signal("legacy_event")
+
+
+func _ready():
+	connected_event.connect(_on_connected_event)
+
+
+func _on_connected_event():
+	pass
+
+#endregion
+
+#region Bad
+
+signal unused_signal
+signal shadowed_signal
+
+
+func shadow():
+	var shadowed_signal = 1
+	print(shadowed_signal)
+
+#endregion
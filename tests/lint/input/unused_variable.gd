@@ -0,0 +1,43 @@
+#region Good
+
+func good():
+	var used = 1
+	var _ignored = 2
+	print(used)
+
+
+func good_in_lambda():
+	var captured = 10
+	var callback = func(): return captured
+	callback.call()
+
+
+func good_in_loop(items):
+	var total = 0
+	for item in items:
+		total += item
+	return total
+
+#endregion
+
+#region Bad
+
+func bad():
+	var unused = 1
+	var speed = 2
+	print(self.speed)
+
+
+func bad_shadowed():
+	var value = 1
+	var callback = func(value): return value
+	callback.call(2)
+
+
+func bad_in_block(condition):
+	if condition:
+		var result = 1
+	else:
+		print("no result")
+
+#endregion
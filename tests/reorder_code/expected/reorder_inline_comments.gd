@@ -0,0 +1,9 @@
+extends Node
+
+const MAX_SPEED = 20
+
+var speed = 10 # Speed in pixels
+
+
+func b():
+	pass # Last line of b
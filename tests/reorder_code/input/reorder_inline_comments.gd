@@ -0,0 +1,9 @@
+extends Node
+
+
+func b():
+	pass # Last line of b
+
+
+var speed = 10 # Speed in pixels
+const MAX_SPEED = 20